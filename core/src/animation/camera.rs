@@ -0,0 +1,330 @@
+use crate::{
+    archives::header::CAMERA,
+    maths::{Easing, Vec2},
+};
+use std::{fmt::Display, str::FromStr};
+
+/// Smallest camera side, in canvas pixels
+pub const MIN_SIZE: u32 = 64;
+
+/// Video camera rectangle, the exported area of the canvas.
+/// Its size is the exported resolution, in canvas pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    /// Top left corner relative to the canvas center
+    pub position: Vec2,
+    pub size: (u32, u32),
+}
+
+/// Camera placement on a frame. Keyed on some frames and interpolated on the others,
+/// so the camera can pan, zoom and roll over time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shot {
+    /// Point shown on the output center, relative to the canvas center
+    pub center: Vec2,
+    /// Magnification, 2 shows half of the camera area
+    pub zoom: f32,
+    /// Camera roll in degrees, clockwise
+    pub rotation: f32,
+}
+
+/// Common output resolutions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraPreset {
+    DEFAULT,
+    HD,
+    FULL_HD,
+    SQUARE,
+    VERTICAL,
+}
+
+impl CameraPreset {
+    pub const ALL: [CameraPreset; 5] = [
+        CameraPreset::DEFAULT,
+        CameraPreset::HD,
+        CameraPreset::FULL_HD,
+        CameraPreset::SQUARE,
+        CameraPreset::VERTICAL,
+    ];
+
+    pub fn size(&self) -> (u32, u32) {
+        match self {
+            CameraPreset::DEFAULT => CAMERA,
+            CameraPreset::HD => (1280, 720),
+            CameraPreset::FULL_HD => (1920, 1080),
+            CameraPreset::SQUARE => (1080, 1080),
+            CameraPreset::VERTICAL => (1080, 1920),
+        }
+    }
+
+    /// Preset of given camera size, if any
+    pub fn find(size: (u32, u32)) -> Option<CameraPreset> {
+        CameraPreset::ALL
+            .into_iter()
+            .find(|preset| preset.size() == size)
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Camera {
+        Camera::centered(CAMERA)
+    }
+}
+
+impl Camera {
+    /// Camera of given size on the canvas center
+    pub fn centered(size: (u32, u32)) -> Camera {
+        Camera {
+            position: Vec2::new(-(size.0 as f32) / 2.0, -(size.1 as f32) / 2.0),
+            size,
+        }
+    }
+
+    pub fn center(&self) -> Vec2 {
+        self.position.add(Vec2::new(
+            self.size.0 as f32 / 2.0,
+            self.size.1 as f32 / 2.0,
+        ))
+    }
+
+    /// Change size keeping the camera center
+    pub fn resize(&mut self, size: (u32, u32)) {
+        let center = self.center();
+        self.size = size;
+        self.position = center.sub(Vec2::new(size.0 as f32 / 2.0, size.1 as f32 / 2.0));
+    }
+
+    /// Change size keeping the top left corner, sides are even
+    /// and at least `MIN_SIZE`, as video encoders require.
+    pub fn resize_to(&mut self, corner: Vec2) {
+        let size = corner.sub(self.position);
+        let side = |value: f32| ((value.max(MIN_SIZE as f32) as u32) / 2) * 2;
+
+        self.size = (side(size.x), side(size.y));
+    }
+
+    /// Keep the camera inside a canvas of given size, sides stay even
+    pub fn clamp_to(&mut self, canvas: (u32, u32)) {
+        let side = |size: u32, canvas: u32| (size.min(canvas) / 2) * 2;
+        self.size = (side(self.size.0, canvas.0), side(self.size.1, canvas.1));
+
+        let min = Vec2::new(-(canvas.0 as f32) / 2.0, -(canvas.1 as f32) / 2.0);
+        let max = min
+            .scale_by(-1.0)
+            .sub(Vec2::new(self.size.0 as f32, self.size.1 as f32));
+
+        self.position = Vec2::new(
+            self.position.x.clamp(min.x, max.x),
+            self.position.y.clamp(min.y, max.y),
+        );
+    }
+
+    /// Shot of frames without camera keys
+    pub fn shot(&self) -> Shot {
+        Shot {
+            center: self.center(),
+            zoom: 1.0,
+            rotation: 0.0,
+        }
+    }
+
+    /// x, y, width and height relative to the canvas center
+    pub fn rect(&self) -> (f32, f32, f32, f32) {
+        (
+            self.position.x,
+            self.position.y,
+            self.size.0 as f32,
+            self.size.1 as f32,
+        )
+    }
+}
+
+impl Shot {
+    /// Zoom is interpolated geometrically, so zooming feels constant
+    pub fn lerp(&self, to: Shot, amount: f32) -> Shot {
+        Shot {
+            center: self.center.lerp(to.center, amount),
+            zoom: self.zoom * (to.zoom / self.zoom).powf(amount),
+            rotation: self.rotation + (to.rotation - self.rotation) * amount,
+        }
+    }
+
+    /// Map an output pixel offset from the output center into the canvas
+    pub fn to_canvas(&self, offset: Vec2) -> Vec2 {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let offset = offset.scale_by(1.0 / self.zoom);
+
+        self.center.add(Vec2::new(
+            offset.x * cos - offset.y * sin,
+            offset.x * sin + offset.y * cos,
+        ))
+    }
+
+    /// Map a canvas point into an output pixel offset from the output center
+    pub fn to_output(&self, point: Vec2) -> Vec2 {
+        let (sin, cos) = (-self.rotation).to_radians().sin_cos();
+        let offset = point.sub(self.center).scale_by(self.zoom);
+
+        Vec2::new(
+            offset.x * cos - offset.y * sin,
+            offset.x * sin + offset.y * cos,
+        )
+    }
+}
+
+/// Shot of a frame from camera keys given as (frame index, shot, easing)
+/// ordered by frame. Frames before the first key and after the last one hold it.
+pub fn shot_at(base: Shot, keys: &[(usize, Shot, Easing)], index: usize) -> Shot {
+    let next = keys.iter().position(|(frame, _, _)| *frame > index);
+
+    match next {
+        _ if keys.is_empty() => base,
+        Some(0) => keys[0].1,
+        None => keys[keys.len() - 1].1,
+        Some(next) => {
+            let (from, shot, easing) = keys[next - 1];
+            let (to, next_shot, _) = keys[next];
+            let amount = easing.apply((index - from) as f32 / (to - from) as f32);
+
+            shot.lerp(next_shot, amount)
+        }
+    }
+}
+
+/// Written as `<center x>,<center y> <zoom> <rotation>`
+impl Display for Shot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{},{} {} {}",
+            self.center.x, self.center.y, self.zoom, self.rotation
+        )
+    }
+}
+
+impl FromStr for Shot {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut words = value.split_whitespace();
+        let (x, y) = words.next().ok_or(())?.split_once(',').ok_or(())?;
+        let number = |word: Option<&str>| {
+            word.ok_or(())?
+                .parse::<f32>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .ok_or(())
+        };
+
+        let shot = Shot {
+            center: Vec2::new(number(Some(x))?, number(Some(y))?),
+            zoom: number(words.next())?,
+            rotation: number(words.next())?,
+        };
+
+        if shot.zoom <= 0.0 {
+            return Err(());
+        }
+
+        Ok(shot)
+    }
+}
+
+/// Written as `<x>,<y> <width>x<height>`
+impl Display for Camera {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{},{} {}x{}",
+            self.position.x, self.position.y, self.size.0, self.size.1
+        )
+    }
+}
+
+impl FromStr for Camera {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (position, size) = value.trim().split_once(' ').ok_or(())?;
+        let (x, y) = position.split_once(',').ok_or(())?;
+        let (width, height) = size.split_once('x').ok_or(())?;
+        let side = |value: &str| {
+            value
+                .parse::<u32>()
+                .ok()
+                .filter(|side| *side >= MIN_SIZE && side % 2 == 0)
+                .ok_or(())
+        };
+
+        Ok(Camera {
+            position: Vec2::new(x.parse().map_err(|_| ())?, y.parse().map_err(|_| ())?),
+            size: (side(width)?, side(height)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camera_stays_inside_canvas() {
+        let mut camera = Camera::default();
+        camera.resize_to(Vec2::new(100.5, -350.0));
+        assert_eq!(camera.size, (640, 64));
+
+        camera.resize(CameraPreset::VERTICAL.size());
+        camera.position.x = 5000.0;
+        camera.clamp_to((1920, 1080));
+
+        assert_eq!(camera.size, (1080, 1080));
+        assert_eq!(camera.position, Vec2::new(-120.0, -540.0));
+
+        assert!("0,0 1080x720".parse::<Camera>().is_ok());
+        assert!("0,0 0x0".parse::<Camera>().is_err());
+        assert!("0,0 1081x720".parse::<Camera>().is_err());
+    }
+
+    #[test]
+    fn shots_are_interpolated_between_keys() {
+        let base = Camera::default().shot();
+        let wide = Shot {
+            center: Vec2::new(100.0, 0.0),
+            zoom: 1.0,
+            rotation: 0.0,
+        };
+        let close = Shot {
+            center: Vec2::new(200.0, 50.0),
+            zoom: 4.0,
+            rotation: 90.0,
+        };
+        let keys = [(1, wide, Easing::LINEAR), (3, close, Easing::LINEAR)];
+
+        assert_eq!(shot_at(base, &[], 2), base);
+        assert_eq!(shot_at(base, &keys, 0), wide);
+        assert_eq!(shot_at(base, &keys, 5), close);
+
+        let middle = shot_at(base, &keys, 2);
+        assert_eq!(middle.center, Vec2::new(150.0, 25.0));
+        assert_eq!((middle.zoom, middle.rotation), (2.0, 45.0));
+
+        let point = Vec2::new(30.0, -12.0);
+        let back = close.to_canvas(close.to_output(point));
+        assert!(back.distance_to(point) < 0.001);
+        assert_eq!("200,50 4 90".parse::<Shot>(), Ok(close));
+    }
+
+    #[test]
+    fn shots_with_invalid_numbers_are_rejected() {
+        for shot in [
+            "0,0 0 0",
+            "0,0 -1 0",
+            "NaN,0 1 0",
+            "0,inf 1 0",
+            "0,0 NaN 0",
+            "0,0 1 -inf",
+        ] {
+            assert!(shot.parse::<Shot>().is_err(), "{} should be rejected", shot);
+        }
+    }
+}
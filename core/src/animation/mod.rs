@@ -0,0 +1,158 @@
+pub mod camera;
+
+use self::camera::Shot;
+use crate::{archives::header::Header, figure::Figure, maths::*};
+use std::collections::HashMap;
+
+/// Edges (start, end) of a posed figure, by edge index
+pub type FigureState = HashMap<usize, (Vec2, Vec2)>;
+
+/// Static figure states of an animation frame.
+/// In files, coordinates are relative to the animation canvas center.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameData {
+    /// Keyframes are posed by hand, frames in between two keyframes are tweened.
+    pub keyframe: bool,
+    /// Timing curve from this keyframe to the next one.
+    pub easing: Easing,
+    /// Camera key of this frame
+    pub camera: Option<Shot>,
+    /// Ticks this frame stays on screen, a tick lasts `1 / fps` seconds
+    pub hold: u32,
+    /// Figure global index and its edges (start, end) ordered by edge index
+    pub states: Vec<(usize, FigureState)>,
+}
+
+/// Animation timeline, without any render state
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationData {
+    pub header: Header,
+    pub figures: Vec<Figure>,
+    pub frames: Vec<FrameData>,
+}
+
+impl FrameData {
+    /// Static state of a figure in this frame
+    pub fn state(&self, global_index: usize) -> Option<&HashMap<usize, (Vec2, Vec2)>> {
+        self.states
+            .iter()
+            .find(|(index, _)| *index == global_index)
+            .map(|(_, state)| state)
+    }
+}
+
+impl AnimationData {
+    /// Camera of every frame, interpolated in between camera keys
+    /// with the easing of keyframes.
+    pub fn shots(&self) -> Vec<Shot> {
+        let keys: Vec<_> = self
+            .frames
+            .iter()
+            .enumerate()
+            .filter_map(|(index, frame)| {
+                let easing = if frame.keyframe {
+                    frame.easing
+                } else {
+                    Easing::LINEAR
+                };
+
+                frame.camera.map(|shot| (index, shot, easing))
+            })
+            .collect();
+
+        (0..self.frames.len())
+            .map(|index| camera::shot_at(self.header.camera.shot(), &keys, index))
+            .collect()
+    }
+
+    /// Generate the poses of every frame in between two keyframes.
+    /// Only figures present in both keyframes are tweened.
+    /// Returns the indexes of frames whose poses have changed.
+    pub fn tween(&mut self) -> Vec<usize> {
+        let mut changed_frames = vec![];
+        let keyframes: Vec<usize> = (0..self.frames.len())
+            .filter(|i| self.frames[*i].keyframe)
+            .collect();
+
+        for pair in keyframes.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let easing = self.frames[from].easing;
+
+            for index in (from + 1)..to {
+                let amount = easing.apply((index - from) as f32 / (to - from) as f32);
+                let mut changed = false;
+
+                for local in 0..self.frames[index].states.len() {
+                    let global_index = self.frames[index].states[local].0;
+
+                    let state = match (
+                        self.frames[from].state(global_index),
+                        self.frames[to].state(global_index),
+                    ) {
+                        (Some(start), Some(end)) => {
+                            self.figures[global_index].interpolate_state(start, end, amount)
+                        }
+                        _ => continue,
+                    };
+
+                    if self.frames[index].states[local].1 != state {
+                        self.frames[index].states[local].1 = state;
+                        changed = true;
+                    }
+                }
+
+                if changed {
+                    changed_frames.push(index);
+                }
+            }
+        }
+
+        changed_frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{archives::header::FileKind, figure::edge::Edge};
+
+    #[test]
+    fn tween_in_between_frames() {
+        let figure = Figure::new(vec![
+            Edge::new(Vec2::new(0.0, 0.0), Vec2::new(0.0, -100.0), -1, 1),
+            Edge::new(Vec2::new(0.0, -100.0), Vec2::new(0.0, -200.0), 0, 1),
+        ]);
+        let mut posed = figure.clone();
+        posed.tree[0].end = Vec2::new(100.0, 0.0);
+        posed.tree[1].start = Vec2::new(100.0, 0.0);
+        posed.tree[1].end = Vec2::new(200.0, 0.0);
+
+        let frame = |keyframe: bool, figure: &Figure| FrameData {
+            keyframe,
+            easing: Easing::LINEAR,
+            camera: None,
+            hold: 1,
+            states: vec![(0, figure.scan())],
+        };
+
+        let mut animation = AnimationData {
+            header: Header::new(FileKind::ANIMATION),
+            frames: vec![
+                frame(true, &figure),
+                frame(false, &figure),
+                frame(true, &posed),
+            ],
+            figures: vec![figure],
+        };
+
+        assert_eq!(animation.tween(), vec![1]);
+
+        let state = animation.frames[1].state(0).unwrap();
+        assert_eq!(state[&0], (Vec2::new(0.0, 0.0), Vec2::new(71.0, -71.0)));
+        // Children follow the rotated parent joint
+        assert_eq!(state[&1].0, Vec2::new(71.0, -71.0));
+
+        // Nothing changes when tweening again
+        assert!(animation.tween().is_empty());
+    }
+}
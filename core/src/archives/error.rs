@@ -0,0 +1,60 @@
+use std::{fmt::Display, io};
+
+pub type Result<T> = std::result::Result<T, VetorError>;
+
+#[derive(Debug)]
+pub enum VetorError {
+    /// File could not be read or written
+    IO { path: String, error: io::Error },
+    /// Compressed content is corrupted
+    DECODE { error: io::Error },
+    /// Content cannot be written in the requested format
+    ENCODE { message: String },
+    /// Invalid content at a 1-based line and column of the decoded file
+    PARSE {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl VetorError {
+    pub fn io(path: &str, error: io::Error) -> VetorError {
+        VetorError::IO {
+            path: path.to_owned(),
+            error,
+        }
+    }
+
+    /// Parse error located at `field`, which must be a slice of `source`
+    pub fn parse(source: &str, field: &str, message: impl Into<String>) -> VetorError {
+        let offset = (field.as_ptr() as usize)
+            .saturating_sub(source.as_ptr() as usize)
+            .min(source.len());
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+
+        VetorError::PARSE {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for VetorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VetorError::IO { path, error } => write!(f, "{}: {}", path, error),
+            VetorError::DECODE { error } => write!(f, "Cannot decode file: {}", error),
+            VetorError::ENCODE { message } => write!(f, "Cannot encode file: {}", message),
+            VetorError::PARSE {
+                line,
+                column,
+                message,
+            } => write!(f, "Line {}, column {}: {}", line, column, message),
+        }
+    }
+}
+
+impl std::error::Error for VetorError {}
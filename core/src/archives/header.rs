@@ -0,0 +1,207 @@
+use super::error::{Result, VetorError};
+use crate::{
+    animation::camera::{Camera, MIN_SIZE},
+    log,
+};
+use std::{
+    env,
+    fmt::Display,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Magic word starting every versioned Vetor file
+pub const MAGIC: &str = "VETOR";
+/// Current file format version, header-less files are version 1
+pub const FORMAT_VERSION: u32 = 2;
+/// Canvas size of files without canvas metadata
+pub const CANVAS: (u32, u32) = (1080 * 5, 720 * 5);
+/// Video camera size, centered on the canvas
+pub const CAMERA: (u32, u32) = (1080, 720);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileKind {
+    FIGURE,
+    ANIMATION,
+}
+
+impl Display for FileKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileKind::FIGURE => write!(f, "FIGURE"),
+            FileKind::ANIMATION => write!(f, "ANIMATION"),
+        }
+    }
+}
+
+/// File header with format version and metadata block.
+///
+/// ```text
+/// VETOR ANIMATION 2
+/// #author leandro
+/// #created 2023-06-01
+/// #canvas 5400x3600
+/// #fps 5
+/// #camera -540,-360 1080x720
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+    pub kind: FileKind,
+    pub version: u32,
+    pub author: String,
+    pub created: String,
+    pub canvas: (u32, u32),
+    pub fps: f32,
+    /// Exported area of animations
+    pub camera: Camera,
+}
+
+impl Header {
+    /// Header for a file created now by the current user
+    pub fn new(kind: FileKind) -> Header {
+        let author = env::var("USER")
+            .or_else(|_| env::var("USERNAME"))
+            .unwrap_or_default();
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or(0);
+
+        Header {
+            kind,
+            version: FORMAT_VERSION,
+            author,
+            created: date(seconds),
+            canvas: CANVAS,
+            fps: 5.0,
+            camera: Camera::default(),
+        }
+    }
+
+    /// Header assumed for files written before versioning
+    pub fn legacy(kind: FileKind) -> Header {
+        Header {
+            kind,
+            version: 1,
+            author: String::new(),
+            created: String::new(),
+            canvas: CANVAS,
+            fps: 5.0,
+            camera: Camera::default(),
+        }
+    }
+
+    /// Split raw file content into its header and body.
+    /// Header-less files are read as version 1 with default metadata.
+    pub fn parse(raw: &str, kind: FileKind) -> Result<(Header, &str)> {
+        let (first, mut body) = raw.split_once('\n').unwrap_or((raw, ""));
+        let mut words = first.split_whitespace();
+
+        if words.next() != Some(MAGIC) {
+            return Ok((Header::legacy(kind), raw));
+        }
+
+        let mut header = Header::legacy(kind);
+        header.kind = match words.next() {
+            Some("FIGURE") => FileKind::FIGURE,
+            Some("ANIMATION") => FileKind::ANIMATION,
+            _ => kind,
+        };
+        header.version = words
+            .next()
+            .and_then(|version| version.parse().ok())
+            .unwrap_or(FORMAT_VERSION);
+
+        if header.version > FORMAT_VERSION {
+            log!(
+                "File version {} is newer than {}, reading it anyway",
+                header.version,
+                FORMAT_VERSION
+            );
+        }
+
+        // Metadata block, each line as "#<key> <value>"
+        while body.starts_with('#') {
+            let (line, rest) = body.split_once('\n').unwrap_or((body, ""));
+            let (key, value) = line[1..].split_once(' ').unwrap_or((&line[1..], ""));
+            let value = value.trim();
+
+            match key {
+                "author" => header.author = value.to_owned(),
+                "created" => header.created = value.to_owned(),
+                "canvas" => {
+                    let side = |side: &str| side.parse::<u32>().ok().filter(|s| *s >= MIN_SIZE);
+
+                    header.canvas = value
+                        .split_once('x')
+                        .and_then(|(width, height)| Some((side(width)?, side(height)?)))
+                        .ok_or_else(|| {
+                            let message =
+                                format!("canvas must be WxH with sides of at least {}", MIN_SIZE);
+                            VetorError::parse(raw, value, message)
+                        })?
+                }
+                "fps" => {
+                    header.fps = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|fps| fps.is_finite() && *fps > 0.0)
+                        .ok_or_else(|| {
+                            VetorError::parse(raw, value, "fps must be a positive number")
+                        })?
+                }
+                "camera" => {
+                    header.camera = value.parse().map_err(|_| {
+                        let message = format!(
+                            "camera must be x,y WxH with even sides of at least {}",
+                            MIN_SIZE
+                        );
+                        VetorError::parse(raw, value, message)
+                    })?
+                }
+                // Unknown keys are from newer versions
+                _ => (),
+            }
+
+            body = rest;
+        }
+
+        // Cameras larger than the canvas would be cropped out of it
+        header.camera.clamp_to(header.canvas);
+
+        Ok((header, body))
+    }
+}
+
+impl Display for Header {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Files are always written with the current version
+        writeln!(f, "{} {} {}", MAGIC, self.kind, FORMAT_VERSION)?;
+        writeln!(f, "#author {}", self.author.replace('\n', " "))?;
+        writeln!(f, "#created {}", self.created.replace('\n', " "))?;
+        writeln!(f, "#canvas {}x{}", self.canvas.0, self.canvas.1)?;
+        writeln!(f, "#fps {}", self.fps)?;
+
+        if self.kind == FileKind::ANIMATION {
+            writeln!(f, "#camera {}", self.camera)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Format unix seconds as an UTC `YYYY-MM-DD` date
+fn date(seconds: u64) -> String {
+    // Civil from days algorithm, from Howard Hinnant's date library
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
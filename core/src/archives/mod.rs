@@ -0,0 +1,628 @@
+pub mod error;
+pub mod header;
+
+use self::{
+    error::{Result, VetorError},
+    header::{FileKind, Header},
+};
+use crate::{
+    animation::{camera::Shot, AnimationData, FrameData},
+    figure::{edge::*, Figure},
+    maths::{Easing, Rgba, Vec2},
+};
+use flate2::{
+    read::{GzDecoder, ZlibDecoder},
+    write::{GzEncoder, ZlibEncoder},
+    Compression,
+};
+use std::io::prelude::*;
+use std::{cmp::Ordering, collections::HashMap, fs, io::Write};
+
+#[derive(Clone, Debug)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+    pub typ: isize,
+    pub parent: usize,
+    pub index: usize,
+    /// Stroke of the edge ending on the point
+    pub color: Rgba,
+    pub thickness: f32,
+}
+
+#[derive(Clone, Copy)]
+pub enum FileEncoding {
+    RAW,
+    ZLIB,
+    GZIP,
+}
+
+impl FileEncoding {
+    /// Detect encoding from file magic bytes
+    pub fn detect(buffer: &[u8]) -> FileEncoding {
+        match buffer {
+            [0x1f, 0x8b, ..] => FileEncoding::GZIP,
+            // Deflate method with a valid header checksum
+            [cmf, flg, ..]
+                if cmf & 0x0f == 8 && (*cmf as u16 * 256 + *flg as u16).is_multiple_of(31) =>
+            {
+                FileEncoding::ZLIB
+            }
+            _ => FileEncoding::RAW,
+        }
+    }
+}
+
+/// Compress raw file content with given encoding
+pub fn encode(raw: &str, encoding: FileEncoding) -> Vec<u8> {
+    match encoding {
+        FileEncoding::RAW => raw.as_bytes().to_vec(),
+        FileEncoding::GZIP => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(raw.as_bytes()).ok();
+            encoder.finish().unwrap()
+        }
+        FileEncoding::ZLIB => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(raw.as_bytes()).ok();
+            encoder.finish().unwrap()
+        }
+    }
+}
+
+/// Decompress file content into raw text with given encoding
+pub fn decode(buffer: &[u8], encoding: FileEncoding) -> Result<String> {
+    let mut raw = String::new();
+
+    match encoding {
+        FileEncoding::RAW => raw = String::from_utf8_lossy(buffer).into_owned(),
+        FileEncoding::GZIP => {
+            GzDecoder::new(buffer)
+                .read_to_string(&mut raw)
+                .map_err(|error| VetorError::DECODE { error })?;
+        }
+        FileEncoding::ZLIB => {
+            ZlibDecoder::new(buffer)
+                .read_to_string(&mut raw)
+                .map_err(|error| VetorError::DECODE { error })?;
+        }
+    }
+
+    Ok(raw)
+}
+
+/// Read and decode a file with any encoding
+pub fn read(path: &str) -> Result<(String, FileEncoding)> {
+    let file = fs::read(path).map_err(|error| VetorError::io(path, error))?;
+    let encoding = FileEncoding::detect(&file);
+
+    Ok((decode(&file, encoding)?, encoding))
+}
+
+/// Encode and write a file
+pub fn write(path: &str, raw: &str, encoding: FileEncoding) -> Result<()> {
+    fs::write(path, encode(raw, encoding)).map_err(|error| VetorError::io(path, error))
+}
+
+pub fn import_figure(path: &str) -> Result<Figure> {
+    let (raw, _) = read(path)?;
+    let (_, body) = Header::parse(&raw, FileKind::FIGURE)?;
+
+    parse_figure(body, &raw)
+}
+
+pub fn export_figure(path: &str, figure: Figure, encoding: FileEncoding) -> Result<()> {
+    let raw_figure =
+        Header::new(FileKind::FIGURE).to_string() + &points_to_raw(&figure_to_raw(figure));
+
+    write(path, &raw_figure, encoding)
+}
+
+/// One point per row, as `type,x,y,parent,index,color,thickness`
+fn points_to_raw(points: &[Point]) -> String {
+    let mut raw = String::new();
+
+    for point in points {
+        raw += &format!(
+            "{},{},{},{},{},{},{}\n",
+            point.typ, point.x, point.y, point.parent, point.index, point.color, point.thickness
+        );
+    }
+
+    raw
+}
+
+/// Write header, figures and then each frame states.
+///
+/// ```text
+/// <header>
+/// <figure points>
+/// ^
+/// <figure points>
+/// @Frame 0 key ease-in hold 3 camera <center x>,<center y> <zoom> <rotation>
+/// ^<figure global index>
+/// <start x>,<start y>,<end x>,<end y>
+/// ```
+pub fn animation_to_raw(animation: &AnimationData) -> String {
+    let mut raw = animation.header.to_string();
+
+    raw += &animation
+        .figures
+        .iter()
+        .map(|figure| points_to_raw(&figure_to_raw(figure.clone())))
+        .collect::<Vec<_>>()
+        // Add interssection only in between figures
+        .join("^\n");
+
+    for (frame_index, frame) in animation.frames.iter().enumerate() {
+        raw += &format!("@Frame {}", frame_index);
+
+        if frame.keyframe {
+            raw += &format!(" key {}", frame.easing);
+        }
+
+        if frame.hold > 1 {
+            raw += &format!(" hold {}", frame.hold);
+        }
+
+        if let Some(shot) = frame.camera {
+            raw += &format!(" camera {}", shot);
+        }
+
+        raw += "\n";
+
+        for (global_index, state) in &frame.states {
+            raw += &format!("^{}\n", global_index);
+
+            let mut edges: Vec<_> = state.iter().collect();
+            edges.sort_by(|a, b| a.0.cmp(b.0));
+
+            for (_, (start, end)) in edges {
+                raw += &format!("{},{},{},{}\n", start.x, start.y, end.x, end.y);
+            }
+        }
+    }
+
+    raw
+}
+
+/// Parse figures and frame states written by `animation_to_raw`.
+/// Older header-less files with integer coordinates are read the same way.
+pub fn raw_to_animation(source: &str) -> Result<AnimationData> {
+    let (header, raw) = Header::parse(source, FileKind::ANIMATION)?;
+    let split: Vec<_> = raw.split("@Frame").collect();
+    let (figs, frames) = (split[0], &split[1..]);
+
+    let figures = figs
+        .split("^\n")
+        .map(|fig| parse_figure(fig, source))
+        .collect::<Result<Vec<_>>>()?;
+    let mut animation_frames = vec![];
+
+    for frame_str in frames {
+        let (header, body) = frame_str.split_once("\n").unwrap_or((frame_str, ""));
+        let mut frame = FrameData {
+            keyframe: header.split_whitespace().any(|word| word == "key"),
+            easing: header
+                .split_whitespace()
+                .find_map(|word| word.parse::<Easing>().ok())
+                .unwrap_or(Easing::LINEAR),
+            camera: match header.split_once(" camera ") {
+                Some((_, shot)) => Some(shot.parse::<Shot>().map_err(|_| {
+                    VetorError::parse(
+                        source,
+                        shot,
+                        "camera must be <center x>,<center y> <zoom> <rotation>",
+                    )
+                })?),
+                None => None,
+            },
+            hold: match header
+                .split_whitespace()
+                .skip_while(|word| *word != "hold")
+                .nth(1)
+            {
+                Some(ticks) => ticks
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|ticks| *ticks > 0)
+                    .ok_or_else(|| {
+                        VetorError::parse(source, ticks, "hold must be a positive int")
+                    })?,
+                None => 1,
+            },
+            states: vec![],
+        };
+
+        for state in body.split("^") {
+            if state.trim().is_empty() {
+                continue;
+            }
+
+            let mut lines = state.lines();
+            let index_str = lines.next().unwrap_or_default();
+            let index = index_str.trim().parse::<usize>().map_err(|_| {
+                VetorError::parse(source, index_str, "figure index must be a numeric int")
+            })?;
+
+            if index >= figures.len() {
+                return Err(VetorError::parse(
+                    source,
+                    index_str,
+                    format!("figure {} does not exist", index),
+                ));
+            }
+
+            let mut moved_edges = HashMap::new();
+
+            for line in lines {
+                let values: Vec<_> = line.split(",").collect();
+
+                if values.len() < 4 {
+                    continue;
+                }
+
+                if moved_edges.len() >= figures[index].tree.len() {
+                    return Err(VetorError::parse(
+                        source,
+                        line,
+                        format!(
+                            "figure {} has only {} edges",
+                            index,
+                            figures[index].tree.len()
+                        ),
+                    ));
+                }
+
+                let edge = values
+                    .iter()
+                    .map(|value| {
+                        value.trim().parse::<f32>().map_err(|_| {
+                            VetorError::parse(
+                                source,
+                                value,
+                                "edge state must be a numeric float 32",
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<f32>>>()?;
+
+                moved_edges.insert(
+                    moved_edges.len(),
+                    (Vec2::new(edge[0], edge[1]), Vec2::new(edge[2], edge[3])),
+                );
+            }
+
+            frame.states.push((index, moved_edges));
+        }
+
+        animation_frames.push(frame);
+    }
+
+    Ok(AnimationData {
+        header,
+        figures,
+        frames: animation_frames,
+    })
+}
+
+pub fn raw_to_figure(raw: &str) -> Result<Figure> {
+    parse_figure(raw, raw)
+}
+
+/// Parse figure points, locating errors inside the whole `source` file
+fn parse_figure(raw: &str, source: &str) -> Result<Figure> {
+    let mut points: Vec<Point> = Vec::new();
+
+    for row in raw.split('\n').collect::<Vec<_>>() {
+        if row.starts_with("//") {
+            continue;
+        };
+        if row.starts_with("#") {
+            continue;
+        };
+
+        let edge = row.split(',').collect::<Vec<_>>();
+        if edge.len() < 5 {
+            continue;
+        };
+
+        let error = |i: usize, message: &str| VetorError::parse(source, edge[i], message);
+
+        let typ = edge[0]
+            .trim()
+            .parse::<isize>()
+            .map_err(|_| error(0, "type must be a numeric int"))?;
+        let x = edge[1]
+            .trim()
+            .parse::<f32>()
+            .map_err(|_| error(1, "x must be a numeric float 32"))?;
+        let y = edge[2]
+            .trim()
+            .parse::<f32>()
+            .map_err(|_| error(2, "y must be a numeric float 32"))?;
+        let parent = edge[3]
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| error(3, "parent must be a numeric int"))?;
+        let index = edge[4]
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| error(4, "index must be a numeric int"))?;
+
+        // Stroke columns are optional, older files draw every edge the same
+        let color = match edge.get(5) {
+            Some(color) => color
+                .parse::<Rgba>()
+                .map_err(|_| error(5, "color must be a hex RRGGBB or RRGGBBAA"))?,
+            None => COLOR,
+        };
+        let thickness = match edge.get(6) {
+            Some(thickness) => thickness
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|thickness| *thickness > 0.0)
+                .ok_or_else(|| error(6, "thickness must be a positive float 32"))?,
+            None => THICKNESS,
+        };
+
+        if parent >= points.len() && index != 0 {
+            return Err(error(3, "parent must be a previous point"));
+        }
+
+        points.push(Point {
+            x,
+            y,
+            parent,
+            index,
+            typ,
+            color,
+            thickness,
+        });
+    }
+
+    let mut figure_tree: Vec<Edge> = vec![];
+    let mut indexes: Vec<usize> = vec![];
+
+    for i in 0..points.len() {
+        let point = points[i].clone();
+
+        if point.index == 0 {
+            continue;
+        }
+
+        let p1 = points[point.parent].clone();
+        let parent = indexes
+            .iter()
+            .position(|index| *index == point.parent)
+            .map(|x| x as isize);
+
+        indexes.push(point.index);
+
+        let mut edge = Edge::new(
+            Vec2::new(p1.x, p1.y),
+            Vec2::new(point.x, point.y),
+            parent.unwrap_or(-1),
+            point.typ,
+        );
+        edge.color = point.color;
+        edge.thickness = point.thickness;
+        figure_tree.push(edge);
+    }
+
+    if figure_tree.is_empty() {
+        return Err(VetorError::parse(
+            source,
+            raw,
+            "figure must have at least one edge",
+        ));
+    }
+
+    Ok(Figure::new(figure_tree))
+}
+
+pub fn figure_to_raw(mut figure: Figure) -> Vec<Point> {
+    figure.center_to(Vec2::zero());
+    let mut points = HashMap::new();
+    let mut indexes = HashMap::new();
+
+    points.insert(
+        0,
+        Point {
+            x: figure.tree[0].start.x,
+            y: figure.tree[0].start.y,
+            typ: figure.tree[0].format.into(),
+            parent: 0,
+            index: 0,
+            color: COLOR,
+            thickness: THICKNESS,
+        },
+    );
+
+    for (i, edge) in figure.tree.iter().enumerate() {
+        let index = points.len();
+        indexes.insert(i, index);
+
+        points.insert(
+            index,
+            Point {
+                x: edge.end.x,
+                y: edge.end.y,
+                typ: edge.format.into(),
+                parent: if edge.parent == -1 {
+                    0
+                } else {
+                    *indexes.get(&(edge.parent as usize)).unwrap()
+                },
+                index,
+                color: edge.color,
+                thickness: edge.thickness,
+            },
+        );
+    }
+
+    let mut pts: Vec<Point> = points.values().cloned().collect();
+
+    pts.sort_by(|p1, p2| {
+        let diff = p1.index as i32 - p2.index as i32;
+
+        if diff == 0 {
+            return Ordering::Equal;
+        } else if diff < 0 {
+            return Ordering::Less;
+        }
+
+        Ordering::Greater
+    });
+
+    pts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::camera::{Camera, CameraPreset};
+
+    const ANIMATION: &str = include_str!("../../tests/fixtures/unnamed.var");
+
+    #[test]
+    fn legacy_animation_is_upgraded() {
+        let legacy = raw_to_animation(ANIMATION).unwrap();
+        assert_eq!(legacy.header, Header::legacy(FileKind::ANIMATION));
+        assert!(legacy.frames.len() > 1);
+
+        let raw = animation_to_raw(&legacy);
+        assert!(raw.starts_with(&format!("VETOR ANIMATION {}\n", header::FORMAT_VERSION)));
+
+        let upgraded = raw_to_animation(&raw).unwrap();
+        assert_eq!(upgraded.header.version, header::FORMAT_VERSION);
+        assert_eq!(upgraded.figures, legacy.figures);
+        assert_eq!(upgraded.frames, legacy.frames);
+    }
+
+    #[test]
+    fn header_metadata_round_trip() {
+        let mut header = Header::new(FileKind::FIGURE);
+        header.author = String::from("Vetor Studio");
+        header.canvas = (1080, 720);
+        header.fps = 12.5;
+
+        let raw = header.to_string() + "1,0,0,0,0\n";
+        let (parsed, body) = Header::parse(&raw, FileKind::ANIMATION).unwrap();
+
+        assert_eq!(parsed, header);
+        assert_eq!(body, "1,0,0,0,0\n");
+
+        let mut header = Header::new(FileKind::ANIMATION);
+        header.camera = Camera::centered(CameraPreset::VERTICAL.size());
+        header.camera.position.x += 12.5;
+
+        let raw = header.to_string();
+        assert!(raw.contains("#camera -527.5,-960 1080x1920\n"));
+        assert_eq!(Header::parse(&raw, FileKind::ANIMATION).unwrap().0, header);
+    }
+
+    #[test]
+    fn encoding_is_detected() {
+        assert!(matches!(
+            FileEncoding::detect(ANIMATION.as_bytes()),
+            FileEncoding::RAW
+        ));
+        assert!(matches!(FileEncoding::detect(&[]), FileEncoding::RAW));
+
+        for encoding in [FileEncoding::RAW, FileEncoding::GZIP, FileEncoding::ZLIB] {
+            let buffer = encode(ANIMATION, encoding);
+            let detected = FileEncoding::detect(&buffer);
+
+            assert_eq!(detected as i32, encoding as i32);
+            assert_eq!(decode(&buffer, detected).unwrap(), ANIMATION);
+        }
+    }
+
+    #[test]
+    fn animation_round_trip() {
+        let animation =
+            raw_to_animation(&animation_to_raw(&raw_to_animation(ANIMATION).unwrap())).unwrap();
+
+        for encoding in [FileEncoding::RAW, FileEncoding::GZIP, FileEncoding::ZLIB] {
+            let buffer = encode(&animation_to_raw(&animation), encoding);
+            let loaded = raw_to_animation(&decode(&buffer, encoding).unwrap()).unwrap();
+
+            assert_eq!(loaded, animation);
+        }
+    }
+
+    #[test]
+    fn animation_round_trip_keeps_fractional_poses() {
+        let mut animation = raw_to_animation(ANIMATION).unwrap();
+        animation.header = Header::new(FileKind::ANIMATION);
+        animation.frames[0].keyframe = true;
+        animation.frames[0].easing = Easing::BEZIER(0.25, 0.1, 0.25, 1.0);
+        animation.frames[1].hold = 4;
+        animation.frames[2].camera = Some(Shot {
+            center: Vec2::new(-12.5, 40.0),
+            zoom: 1.5,
+            rotation: -10.0,
+        });
+
+        animation.figures[0].tree[1].color = Rgba::new(200, 30, 30, 128);
+        animation.figures[0].tree[1].thickness = 7.5;
+
+        for (_, state) in &mut animation.frames[1].states {
+            for (start, end) in state.values_mut() {
+                start.x += 0.1;
+                end.y -= 1.0 / 3.0;
+            }
+        }
+
+        let loaded = raw_to_animation(&animation_to_raw(&animation)).unwrap();
+
+        assert_eq!(loaded, animation);
+    }
+
+    #[test]
+    fn parse_errors_are_located() {
+        let raw = "1,0,0,0,0\n1,0,-100,0,1\n1,9x,-16,0,2\n";
+
+        match raw_to_figure(raw) {
+            Err(VetorError::PARSE { line, column, .. }) => assert_eq!((line, column), (3, 3)),
+            other => panic!("expected parse error, got {:?}", other),
+        }
+
+        assert!(raw_to_figure("1,0,0,0,0\n1,0,-100,0,1,#ff00,20\n").is_err());
+        assert!(raw_to_figure("1,0,0,0,0\n1,0,-100,0,1,#ff0000,0\n").is_err());
+
+        let raw = animation_to_raw(&raw_to_animation(ANIMATION).unwrap());
+        let broken = raw.replacen("^1\n", "^7\n", 1);
+        let line = broken.lines().position(|line| line == "^7").unwrap() + 1;
+
+        match raw_to_animation(&broken) {
+            Err(VetorError::PARSE {
+                line: error_line, ..
+            }) => assert_eq!(error_line, line),
+            other => panic!("expected parse error, got {:?}", other.map(|_| ())),
+        }
+
+        for fps in ["0", "-5", "NaN", "inf"] {
+            let broken = raw.replacen("#fps 5\n", &format!("#fps {}\n", fps), 1);
+            assert!(raw_to_animation(&broken).is_err());
+        }
+
+        let broken = raw.replacen("#camera -540,-360 1080x720\n", "#camera 0,0 0x0\n", 1);
+        assert!(raw_to_animation(&broken).is_err());
+
+        for canvas in ["0x0", "5400x32", "5400", "wide"] {
+            let broken = raw.replacen("#canvas 5400x3600\n", &format!("#canvas {}\n", canvas), 1);
+            assert!(matches!(
+                raw_to_animation(&broken),
+                Err(VetorError::PARSE { .. })
+            ));
+        }
+
+        assert!(matches!(
+            decode(&[0x1f, 0x8b, 0, 0], FileEncoding::GZIP),
+            Err(VetorError::DECODE { .. })
+        ));
+    }
+}
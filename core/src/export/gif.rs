@@ -0,0 +1,99 @@
+use crate::archives::error::{Result, VetorError};
+use ::gif::{Encoder, EncodingError, Frame, Repeat};
+use std::{fs::File, io::BufWriter};
+
+/// Quantization speed, from 1 (best palette) to 30 (fastest)
+const QUANTIZATION_SPEED: i32 = 10;
+
+/// Animated GIF writer, each frame gets its own quantized palette
+pub struct GifEncoder {
+    path: String,
+    width: u16,
+    height: u16,
+    fps: f32,
+    encoder: Encoder<BufWriter<File>>,
+}
+
+impl GifEncoder {
+    /// Create the GIF file. `loops` is how many times the animation
+    /// repeats after the first play, `None` repeats forever.
+    pub fn create(
+        path: &str,
+        width: u16,
+        height: u16,
+        fps: f32,
+        loops: Option<u16>,
+    ) -> Result<GifEncoder> {
+        let file = File::create(path).map_err(|error| VetorError::io(path, error))?;
+        let mut encoder = Encoder::new(BufWriter::new(file), width, height, &[])
+            .map_err(|error| encoding_error(path, error))?;
+
+        encoder
+            .set_repeat(loops.map(Repeat::Finite).unwrap_or(Repeat::Infinite))
+            .map_err(|error| encoding_error(path, error))?;
+
+        Ok(GifEncoder {
+            path: path.to_owned(),
+            width,
+            height,
+            fps,
+            encoder,
+        })
+    }
+
+    /// Append a frame of `width * height` RGBA pixels, shown during `ticks` frames of the framerate
+    pub fn push(&mut self, pixels: &mut [u8], ticks: u32) -> Result<()> {
+        let mut frame = Frame::from_rgba_speed(self.width, self.height, pixels, QUANTIZATION_SPEED);
+        frame.delay = frame_delay(self.fps, ticks);
+
+        self.encoder
+            .write_frame(&frame)
+            .map_err(|error| encoding_error(&self.path, error))
+    }
+}
+
+/// GIF delays are hundredths of a second, most viewers ignore delays below 2
+pub fn frame_delay(fps: f32, ticks: u32) -> u16 {
+    (100.0 * ticks as f32 / fps)
+        .round()
+        .clamp(2.0, u16::MAX as f32) as u16
+}
+
+fn encoding_error(path: &str, error: EncodingError) -> VetorError {
+    match error {
+        EncodingError::Io(error) => VetorError::io(path, error),
+        EncodingError::Format(error) => VetorError::ENCODE {
+            message: error.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs};
+
+    #[test]
+    fn frames_are_written_with_held_delays() {
+        let path = env::temp_dir().join("vetor_export_test.gif");
+        let path = path.to_str().unwrap();
+
+        let mut encoder = GifEncoder::create(path, 2, 2, 5.0, None).unwrap();
+        encoder.push(&mut [0; 16], 1).unwrap();
+        encoder.push(&mut [255; 16], 3).unwrap();
+        drop(encoder);
+
+        let mut options = ::gif::DecodeOptions::new();
+        options.set_color_output(::gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(File::open(path).unwrap()).unwrap();
+        let mut delays = vec![];
+
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            delays.push(frame.delay);
+        }
+
+        fs::remove_file(path).ok();
+        assert_eq!(delays, vec![20, 60]);
+        assert_eq!(decoder.repeat(), ::gif::Repeat::Infinite);
+    }
+}
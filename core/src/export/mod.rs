@@ -0,0 +1,7 @@
+//! Encoders for rendered animations.
+//! Frames are given as RGBA pixels, row by row, so any renderer can use them.
+
+pub mod gif;
+pub mod png;
+pub mod sheet;
+pub mod svg;
@@ -0,0 +1,34 @@
+use crate::archives::error::{Result, VetorError};
+use ::png::{BitDepth, ColorType, Encoder, EncodingError};
+use std::{fs::File, io::BufWriter, path::Path};
+
+/// Write `width * height` RGBA pixels as a PNG image
+pub fn write(path: &str, width: u32, height: u32, pixels: &[u8]) -> Result<()> {
+    let file = File::create(path).map_err(|error| VetorError::io(path, error))?;
+    let mut encoder = Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_depth(BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(pixels))
+        .map_err(|error| match error {
+            EncodingError::IoError(error) => VetorError::io(path, error),
+            error => VetorError::ENCODE {
+                message: error.to_string(),
+            },
+        })
+}
+
+/// Numbered file of an image sequence, `walk.png` becomes `walk_0003.png`
+pub fn sequence_path(path: &str, index: usize) -> String {
+    let path = Path::new(path);
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("frame");
+
+    path.with_file_name(format!("{}_{:04}.png", stem, index))
+        .to_string_lossy()
+        .into_owned()
+}
@@ -0,0 +1,177 @@
+use super::png;
+use crate::archives::error::{Result, VetorError};
+use std::{fs, path::Path};
+
+/// All frames of an animation packed in a grid, left to right and top to bottom
+pub struct SpriteSheet {
+    pub frame_width: u32,
+    pub frame_height: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub frames: usize,
+    /// Ticks every frame is held, one by default
+    pub holds: Vec<u32>,
+    pub pixels: Vec<u8>,
+}
+
+impl SpriteSheet {
+    /// Empty sheet with a grid as square as possible.
+    /// Fails when the sheet is too large to be written as an image.
+    pub fn new(frame_width: u32, frame_height: u32, frames: usize) -> Result<SpriteSheet> {
+        let columns = (frames as f32).sqrt().ceil().max(1.0) as usize;
+        let rows = frames.div_ceil(columns).max(1);
+
+        let too_large = || VetorError::ENCODE {
+            message: format!(
+                "sprite sheet of {} frames of {}x{} pixels is too large",
+                frames, frame_width, frame_height
+            ),
+        };
+        let width = columns
+            .checked_mul(frame_width as usize)
+            .filter(|width| u32::try_from(*width).is_ok())
+            .ok_or_else(too_large)?;
+        let height = rows
+            .checked_mul(frame_height as usize)
+            .filter(|height| u32::try_from(*height).is_ok())
+            .ok_or_else(too_large)?;
+        let size = width
+            .checked_mul(height)
+            .and_then(|size| size.checked_mul(4))
+            .ok_or_else(too_large)?;
+
+        Ok(SpriteSheet {
+            frame_width,
+            frame_height,
+            columns: columns as u32,
+            rows: rows as u32,
+            frames,
+            holds: vec![1; frames],
+            pixels: vec![0; size],
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.frame_width * self.columns
+    }
+
+    pub fn height(&self) -> u32 {
+        self.frame_height * self.rows
+    }
+
+    /// Frame rectangle (x, y, width, height) in the sheet
+    pub fn rect(&self, index: usize) -> (u32, u32, u32, u32) {
+        let index = index as u32;
+
+        (
+            (index % self.columns) * self.frame_width,
+            (index / self.columns) * self.frame_height,
+            self.frame_width,
+            self.frame_height,
+        )
+    }
+
+    /// Copy frame RGBA pixels into its grid cell
+    pub fn insert(&mut self, index: usize, pixels: &[u8]) {
+        let (x, y, width, height) = self.rect(index);
+        let row_size = width as usize * 4;
+        let sheet_row_size = self.width() as usize * 4;
+
+        for row in 0..height as usize {
+            let from = row * row_size;
+            let to = (y as usize + row) * sheet_row_size + x as usize * 4;
+            self.pixels[to..to + row_size].copy_from_slice(&pixels[from..from + row_size]);
+        }
+    }
+
+    /// JSON atlas with frame rects and durations in milliseconds.
+    ///
+    /// ```json
+    /// {
+    ///   "frames": [
+    ///     { "index": 0, "x": 0, "y": 0, "w": 1080, "h": 720, "duration": 200 }
+    ///   ],
+    ///   "meta": { "image": "walk.png", "w": 1080, "h": 720, "fps": 5 }
+    /// }
+    /// ```
+    pub fn atlas(&self, image: &str, fps: f32) -> String {
+        let mut json = String::from("{\n  \"frames\": [\n");
+
+        for index in 0..self.frames {
+            let (x, y, w, h) = self.rect(index);
+            let separator = if index + 1 < self.frames { "," } else { "" };
+            let duration = (1000.0 * self.holds[index] as f32 / fps).round() as u32;
+
+            json.push_str(&format!(
+                "    {{ \"index\": {}, \"x\": {}, \"y\": {}, \"w\": {}, \"h\": {}, \"duration\": {} }}{}\n",
+                index, x, y, w, h, duration, separator
+            ));
+        }
+
+        json.push_str(&format!(
+            "  ],\n  \"meta\": {{ \"image\": \"{}\", \"w\": {}, \"h\": {}, \"fps\": {} }}\n}}\n",
+            escape(image),
+            self.width(),
+            self.height(),
+            fps
+        ));
+
+        json
+    }
+
+    /// Write the sheet image and its atlas next to it, `walk.png` and `walk.json`
+    pub fn write(&self, path: &str, fps: f32) -> Result<()> {
+        png::write(path, self.width(), self.height(), &self.pixels)?;
+
+        let image = Path::new(path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        let atlas_path = Path::new(path).with_extension("json");
+        let atlas_path = atlas_path.to_string_lossy();
+
+        fs::write(atlas_path.as_ref(), self.atlas(image, fps))
+            .map_err(|error| VetorError::io(&atlas_path, error))
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_packed_in_a_grid() {
+        let mut sheet = SpriteSheet::new(2, 1, 3).unwrap();
+        sheet.insert(0, &[1; 8]);
+        sheet.insert(1, &[2; 8]);
+        sheet.insert(2, &[3; 8]);
+        sheet.holds[1] = 3;
+
+        assert_eq!((sheet.columns, sheet.rows), (2, 2));
+        assert_eq!(sheet.rect(2), (0, 1, 2, 1));
+        assert_eq!(&sheet.pixels[..16], &[[1; 8], [2; 8]].concat()[..]);
+        assert_eq!(&sheet.pixels[16..], &[[3; 8], [0; 8]].concat()[..]);
+
+        let atlas = sheet.atlas("walk.png", 4.0);
+        assert!(atlas
+            .contains("\"index\": 1, \"x\": 2, \"y\": 0, \"w\": 2, \"h\": 1, \"duration\": 750 }"));
+        assert!(atlas.contains(
+            "{ \"index\": 2, \"x\": 0, \"y\": 1, \"w\": 2, \"h\": 1, \"duration\": 250 }\n"
+        ));
+        assert!(
+            atlas.contains("\"meta\": { \"image\": \"walk.png\", \"w\": 4, \"h\": 2, \"fps\": 4 }")
+        );
+    }
+
+    #[test]
+    fn oversized_sheet_is_an_error() {
+        assert!(matches!(
+            SpriteSheet::new(u32::MAX, u32::MAX, 4),
+            Err(VetorError::ENCODE { .. })
+        ));
+    }
+}
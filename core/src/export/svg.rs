@@ -0,0 +1,415 @@
+use crate::{
+    animation::{camera::Shot, AnimationData},
+    figure::{
+        edge::{Edge, EdgeDrawMode, EdgeFormat},
+        Figure,
+    },
+    maths::*,
+};
+
+/// Area of the drawing shown by the SVG: x, y, width and height
+pub type ViewBox = (f32, f32, f32, f32);
+
+/// Single figure, the view box fits its edges
+pub fn figure(figure: &Figure, background: Rgba) -> String {
+    let mut svg = String::new();
+    let view_box = figure_bounds(figure);
+
+    svg.push_str(&open(view_box, background));
+    for edge in figure.tree.iter() {
+        svg.push_str(&edge_element(edge, background, ""));
+    }
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+/// Figures of one animation frame, as seen by the video camera
+pub fn frame(animation: &AnimationData, index: usize, background: Rgba) -> String {
+    let mut svg = String::new();
+    let size = animation.header.camera.size;
+    let shot = animation.shots()[index];
+
+    svg.push_str(&open((0.0, 0.0, size.0 as f32, size.1 as f32), background));
+    svg.push_str(&format!("  <g transform=\"{}\">\n", transform(shot, size)));
+    for (global_index, state) in &animation.frames[index].states {
+        let mut figure = animation.figures[*global_index].clone();
+        figure.load_state(state.clone());
+
+        svg.push_str("  <g>\n");
+        for edge in figure.tree.iter() {
+            svg.push_str(&edge_element(edge, background, ""));
+        }
+        svg.push_str("  </g>\n");
+    }
+    svg.push_str("  </g>\n</svg>\n");
+
+    svg
+}
+
+/// Whole animation, frames are switched by SMIL animations on every edge.
+/// Figures missing from a frame are hidden during it, camera moves
+/// are animated on the groups holding the figures.
+pub fn animation(animation: &AnimationData, background: Rgba) -> String {
+    let mut svg = String::new();
+    // Held frames last longer, so each frame starts after the ticks of the previous ones
+    let holds: Vec<u32> = animation.frames.iter().map(|frame| frame.hold).collect();
+    let ticks = holds.iter().sum::<u32>().max(1);
+    let key_times = (0..holds.len().max(1))
+        .map(|index| {
+            let start: u32 = holds.iter().take(index).sum();
+            (start as f32 / ticks as f32).to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(";");
+    let timing = format!(
+        "dur=\"{}s\" keyTimes=\"{}\" calcMode=\"discrete\" repeatCount=\"indefinite\"",
+        number(ticks as f32 / animation.header.fps),
+        key_times
+    );
+
+    let size = animation.header.camera.size;
+    let shots = animation.shots();
+
+    svg.push_str(&open((0.0, 0.0, size.0 as f32, size.1 as f32), background));
+
+    let groups = if shots.windows(2).all(|pair| pair[0] == pair[1]) {
+        let shot = shots
+            .first()
+            .copied()
+            .unwrap_or(animation.header.camera.shot());
+        svg.push_str(&format!("  <g transform=\"{}\">\n", transform(shot, size)));
+        1
+    } else {
+        svg.push_str(&camera_groups(&shots, size, &timing));
+        4
+    };
+
+    for (global_index, base) in animation.figures.iter().enumerate() {
+        let states: Vec<_> = animation
+            .frames
+            .iter()
+            .map(|frame| frame.state(global_index))
+            .collect();
+
+        if states.iter().all(|state| state.is_none()) {
+            continue;
+        }
+
+        // Hidden frames keep the last shown pose, so edges don't jump around
+        let mut last = states
+            .iter()
+            .flatten()
+            .next()
+            .map(|state| (*state).clone())
+            .unwrap();
+        let figures: Vec<Figure> = states
+            .iter()
+            .map(|state| {
+                if let Some(state) = state {
+                    last = (*state).clone();
+                }
+
+                let mut figure = base.clone();
+                figure.load_state(last.clone());
+                figure
+            })
+            .collect();
+
+        let display = states
+            .iter()
+            .map(|state| if state.is_some() { "inline" } else { "none" })
+            .collect::<Vec<_>>()
+            .join(";");
+
+        svg.push_str("  <g>\n");
+        svg.push_str(&format!(
+            "    <animate attributeName=\"display\" values=\"{}\" {}/>\n",
+            display, timing
+        ));
+
+        for (index, edge) in base.tree.iter().enumerate() {
+            let mut animations = String::new();
+
+            for (attribute, values) in edge_attributes(edge, &figures, index) {
+                animations.push_str(&format!(
+                    "      <animate attributeName=\"{}\" values=\"{}\" {}/>\n",
+                    attribute, values, timing
+                ));
+            }
+
+            svg.push_str(&edge_element(
+                &figures[0].tree[index],
+                background,
+                &animations,
+            ));
+        }
+
+        svg.push_str("  </g>\n");
+    }
+
+    svg.push_str(&"  </g>\n".repeat(groups));
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+/// Canvas to output transform of a shot, for an output of given size
+fn transform(shot: Shot, size: (u32, u32)) -> String {
+    format!(
+        "translate({} {}) rotate({}) scale({}) translate({} {})",
+        number(size.0 as f32 / 2.0),
+        number(size.1 as f32 / 2.0),
+        number(-shot.rotation),
+        shot.zoom,
+        number(-shot.center.x),
+        number(-shot.center.y)
+    )
+}
+
+/// Nested groups applying the shot of every frame, one transform each,
+/// since SMIL can animate a single transform type per element.
+fn camera_groups(shots: &[Shot], size: (u32, u32), timing: &str) -> String {
+    let values = |value: fn(&Shot) -> String| shots.iter().map(value).collect::<Vec<_>>().join(";");
+    let animations = [
+        ("rotate", values(|shot| number(-shot.rotation))),
+        ("scale", values(|shot| shot.zoom.to_string())),
+        (
+            "translate",
+            values(|shot| format!("{} {}", number(-shot.center.x), number(-shot.center.y))),
+        ),
+    ];
+
+    let mut groups = format!(
+        "  <g transform=\"translate({} {})\">\n",
+        number(size.0 as f32 / 2.0),
+        number(size.1 as f32 / 2.0)
+    );
+
+    for (kind, values) in animations {
+        groups.push_str(&format!(
+            "  <g>\n    <animateTransform attributeName=\"transform\" type=\"{}\" values=\"{}\" {}/>\n",
+            kind, values, timing
+        ));
+    }
+
+    groups
+}
+
+/// Per frame values of the edge position attributes
+fn edge_attributes(edge: &Edge, figures: &[Figure], index: usize) -> Vec<(&'static str, String)> {
+    let values = |attribute: fn(&Edge) -> f32| {
+        figures
+            .iter()
+            .map(|figure| number(attribute(&figure.tree[index])))
+            .collect::<Vec<_>>()
+            .join(";")
+    };
+
+    match edge.format {
+        EdgeFormat::LINE => vec![
+            ("x1", values(|edge| edge.start.x)),
+            ("y1", values(|edge| edge.start.y)),
+            ("x2", values(|edge| edge.end.x)),
+            ("y2", values(|edge| edge.end.y)),
+        ],
+        EdgeFormat::CIRCLE => vec![
+            ("cx", values(|edge| circle_center(edge).x)),
+            ("cy", values(|edge| circle_center(edge).y)),
+        ],
+    }
+}
+
+/// SVG element matching `Edge::draw`: lines have rounded or square caps,
+/// circles are rings filled by their draw mode.
+fn edge_element(edge: &Edge, background: Rgba, children: &str) -> String {
+    let (tag, attributes) = match edge.format {
+        EdgeFormat::LINE => {
+            let cap = if edge.draw_mode == EdgeDrawMode::LINE_BORDER_FLAT {
+                "square"
+            } else {
+                "round"
+            };
+
+            (
+                "line",
+                format!(
+                    "x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke-linecap=\"{}\"",
+                    number(edge.start.x),
+                    number(edge.start.y),
+                    number(edge.end.x),
+                    number(edge.end.y),
+                    cap
+                ),
+            )
+        }
+        EdgeFormat::CIRCLE => {
+            let center = circle_center(edge);
+            let fill = match edge.draw_mode {
+                EdgeDrawMode::CIRCLE_CLEAN => paint("fill", background),
+                EdgeDrawMode::CIRCLE_FULL => paint("fill", edge.color),
+                _ => "fill=\"none\"".to_owned(),
+            };
+
+            (
+                "circle",
+                format!(
+                    "cx=\"{}\" cy=\"{}\" r=\"{}\" {}",
+                    number(center.x),
+                    number(center.y),
+                    number(edge.width / 2.0),
+                    fill
+                ),
+            )
+        }
+    };
+
+    let stroke = format!(
+        "{} stroke-width=\"{}\"",
+        paint("stroke", edge.color),
+        number(edge.thickness)
+    );
+
+    if children.is_empty() {
+        format!("    <{} {} {}/>\n", tag, attributes, stroke)
+    } else {
+        format!(
+            "    <{} {} {}>\n{}    </{}>\n",
+            tag, attributes, stroke, children, tag
+        )
+    }
+}
+
+/// Color attribute as `#rrggbb`, with a separate opacity attribute when translucent,
+/// since 8 digit hex colors are not read by every SVG viewer
+fn paint(attribute: &str, color: Rgba) -> String {
+    let rgb = format!(
+        "{}=\"#{:02x}{:02x}{:02x}\"",
+        attribute, color.r, color.g, color.b
+    );
+
+    if color.a == 255 {
+        rgb
+    } else {
+        let opacity = (color.a as f32 / 255.0 * 1000.0).round() / 1000.0;
+        format!("{} {}-opacity=\"{}\"", rgb, attribute, opacity)
+    }
+}
+
+fn circle_center(edge: &Edge) -> Vec2 {
+    vector2_rotate(edge.width / 2.0, edge.start.angle_to(edge.end)).add(edge.end)
+}
+
+fn open(view_box: ViewBox, background: Rgba) -> String {
+    let (x, y, width, height) = view_box;
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\">\n  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>\n",
+        number(x),
+        number(y),
+        number(width),
+        number(height),
+        number(width),
+        number(height),
+        number(x),
+        number(y),
+        number(width),
+        number(height),
+        paint("fill", background)
+    )
+}
+
+/// Edges bounds, grown by the stroke width
+fn figure_bounds(figure: &Figure) -> ViewBox {
+    let mut min = Vec2::new(f32::MAX, f32::MAX);
+    let mut max = Vec2::new(f32::MIN, f32::MIN);
+
+    for edge in figure.tree.iter() {
+        let (points, margin) = match edge.format {
+            EdgeFormat::LINE => (vec![edge.start, edge.end], edge.thickness),
+            EdgeFormat::CIRCLE => (vec![circle_center(edge)], edge.width / 2.0 + edge.thickness),
+        };
+
+        for point in points {
+            min = Vec2::new(min.x.min(point.x - margin), min.y.min(point.y - margin));
+            max = Vec2::new(max.x.max(point.x + margin), max.y.max(point.y + margin));
+        }
+    }
+
+    if figure.tree.is_empty() {
+        return (0.0, 0.0, 1.0, 1.0);
+    }
+
+    (min.x, min.y, max.x - min.x, max.y - min.y)
+}
+
+/// Coordinates with at most two decimals
+fn number(value: f32) -> String {
+    ((value * 100.0).round() / 100.0).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{archives, figure::edge::COLOR};
+
+    #[test]
+    fn edges_keep_caps_fills_and_strokes() {
+        let mut line = Edge::new(Vec2::new(0.0, 0.0), Vec2::new(0.0, 50.0), -1, 1);
+        line.draw_mode = EdgeDrawMode::LINE_BORDER_FLAT;
+        let mut head = Edge::new(Vec2::new(0.0, 0.0), Vec2::new(0.0, -40.0), 0, 2);
+        head.draw_mode = EdgeDrawMode::CIRCLE_CLEAN;
+        head.color = Rgba::new(255, 0, 0, 128);
+        head.thickness = 8.0;
+
+        let background = Rgba::new(245, 245, 245, 255);
+        let svg = figure(&Figure::new(vec![line, head]), background);
+
+        assert!(svg.contains(
+            "<line x1=\"0\" y1=\"0\" x2=\"0\" y2=\"50\" stroke-linecap=\"square\" stroke=\"#000000\" stroke-width=\"20\"/>"
+        ));
+        assert!(svg.contains(
+            "<circle cx=\"0\" cy=\"-20\" r=\"20\" fill=\"#f5f5f5\" stroke=\"#ff0000\" stroke-opacity=\"0.502\" stroke-width=\"8\"/>"
+        ));
+    }
+
+    #[test]
+    fn animation_switches_frames() {
+        let raw = include_str!("../../tests/fixtures/unnamed.var");
+        let mut data = archives::raw_to_animation(raw).unwrap();
+        let svg = animation(&data, COLOR);
+
+        let frames = data.frames.len();
+        let values = svg
+            .split("attributeName=\"x1\" values=\"")
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .unwrap();
+
+        assert_eq!(values.split(';').count(), frames);
+        assert!(svg.contains(&format!(
+            "dur=\"{}s\"",
+            number(frames as f32 / data.header.fps)
+        )));
+        assert!(!svg.contains("animateTransform"));
+
+        // A single key holds the camera on every frame, two of them move it
+        data.frames[0].camera = Some(data.header.camera.shot());
+        data.frames[1].camera = Some(Shot {
+            center: Vec2::new(10.0, 20.0),
+            zoom: 2.0,
+            rotation: 90.0,
+        });
+        let svg = animation(&data, COLOR);
+        let scales = svg
+            .split("type=\"scale\" values=\"")
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .unwrap();
+
+        assert_eq!(scales.split(';').count(), frames);
+        assert!(frame(&data, 1, COLOR).contains(
+            "<g transform=\"translate(540 360) rotate(-90) scale(2) translate(-10 -20)\">"
+        ));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{input::Pointer, maths::*};
+
+/// Radius of the draggable edge points, in canvas pixels
+pub const POINT_RADIUS: f32 = 5.0;
+/// Stroke color and width of new edges
+pub const COLOR: Rgba = Rgba::new(0, 0, 0, 255);
+pub const THICKNESS: f32 = 20.0;
+
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum EdgeFormat {
+    LINE = 1,
+    CIRCLE = 2,
+}
+
+impl From<isize> for EdgeFormat {
+    fn from(value: isize) -> Self {
+        match value {
+            1 => EdgeFormat::LINE,
+            2 => EdgeFormat::CIRCLE,
+            _ => EdgeFormat::LINE,
+        }
+    }
+}
+
+impl From<EdgeFormat> for isize {
+    fn from(format: EdgeFormat) -> isize {
+        match format {
+            EdgeFormat::LINE => 1,
+            EdgeFormat::CIRCLE => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub struct EdgeDrawOption {
+    pub point: bool,
+    /// Override edge color, used to draw faded copies of figures.
+    pub tint: Option<Rgba>,
+}
+
+impl Default for EdgeDrawOption {
+    fn default() -> EdgeDrawOption {
+        EdgeDrawOption::new()
+    }
+}
+
+impl EdgeDrawOption {
+    pub fn new() -> EdgeDrawOption {
+        EdgeDrawOption {
+            point: true,
+            tint: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum EdgeDrawMode {
+    DEFAULT = 1,
+    LINE_BORDER_FLAT = 2,
+    CIRCLE_FULL = 3,
+    CIRCLE_CLEAN = 4,
+}
+
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub struct Edge {
+    pub start: Vec2,
+    pub end: Vec2,
+    pub pressed_start: bool,
+    pub pressed_end: bool,
+    pub width: f32,
+    pub moved: bool,
+    pub fixed_angle: f32,
+    pub moved_angle: f32,
+    pub parent: isize,
+    pub format: EdgeFormat,
+    pub draw_mode: EdgeDrawMode,
+    pub color: Rgba,
+    /// Stroke width of lines and rings, in canvas pixels
+    pub thickness: f32,
+}
+
+impl Edge {
+    pub fn new(start: Vec2, end: Vec2, parent: isize, typ: isize) -> Edge {
+        Edge {
+            start,
+            end,
+            parent,
+            format: EdgeFormat::from(typ),
+            pressed_start: false,
+            pressed_end: false,
+            moved: false,
+            moved_angle: 0.0,
+            fixed_angle: end.angle_to(start),
+            width: start.distance_to(end),
+            draw_mode: EdgeDrawMode::DEFAULT,
+            color: COLOR,
+            thickness: THICKNESS,
+        }
+    }
+
+    /// Calculate real end position rotating on fixed angle
+    /// with current width and then sum with start vector.
+    pub fn get_real_end(&self) -> Vec2 {
+        vector2_rotate(self.width, self.fixed_angle).add(self.start)
+    }
+
+    pub fn update_angle(&mut self) {
+        self.fixed_angle = self.end.angle_to(self.start);
+    }
+
+    pub fn update(
+        &mut self,
+        pointer: &Pointer,
+        line_tree: &[Edge],
+        point_pressed: &mut bool,
+        pressed_root: &mut bool,
+        start_position: Vec2,
+    ) -> Edge {
+        // Discount start position to align with texture position, then undo the view zoom
+        let mouse_pos = pointer
+            .position
+            .sub(start_position)
+            .scale_by(1.0 / pointer.zoom);
+        // Zoomed out points keep their screen size, so they can still be grabbed
+        let radius = POINT_RADIUS / pointer.zoom.min(1.0);
+        self.moved = false;
+        self.moved_angle = 0.0;
+
+        if check_collision_point_circle(mouse_pos, self.end, radius) && !*point_pressed {
+            self.pressed_end = true;
+            *point_pressed = true;
+        }
+
+        // Check if point is collided and if root point is pressed
+        if check_collision_point_circle(mouse_pos, self.start, radius)
+            && self.parent == -1
+            && (!*point_pressed || *pressed_root)
+        {
+            self.pressed_start = true;
+            *point_pressed = true;
+            *pressed_root = true;
+        }
+
+        if pointer.down {
+            // For each non root edges
+            if self.parent >= 0 {
+                let parent = &line_tree[self.parent as usize];
+
+                let end = self.end;
+                let start = self.start;
+                let parent_angle = parent.end.angle_to(parent.start);
+
+                // Get current static angle or rotate with parent.
+                let angle = if parent.moved {
+                    self.moved = true;
+                    parent_angle - parent.fixed_angle + self.fixed_angle
+                } else {
+                    end.angle_to(start)
+                };
+
+                self.start = parent.end;
+                self.end = vector2_rotate(self.width, angle).add(self.start);
+            }
+
+            // Only root edges
+            if self.parent < 0 && self.pressed_start {
+                let angle = self.end.angle_to(self.start);
+                self.start = mouse_pos;
+                self.end = vector2_rotate(self.width, angle).add(self.start);
+            }
+
+            if self.pressed_end {
+                let angle = mouse_pos.angle_to(self.start);
+                self.moved = true;
+                self.end = vector2_rotate(self.width, angle).add(self.start);
+            }
+        }
+
+        // Clear pressed variables when mouse is not pressed anymore
+        if !pointer.down {
+            // Caculate a diference of the rotated angles.
+            if self.pressed_end {
+                let angle = self.fixed_angle;
+                self.fixed_angle = self.end.angle_to(self.start);
+                self.moved_angle = self.fixed_angle - angle;
+            }
+
+            // Recalculate rotated angles to children.
+            if self.parent >= 0 {
+                let parent = &line_tree[self.parent as usize];
+
+                if parent.moved_angle != 0.0 {
+                    let angle = self.fixed_angle;
+                    self.fixed_angle += parent.moved_angle;
+                    self.moved_angle = self.fixed_angle - angle;
+                }
+            }
+
+            self.pressed_end = false;
+            self.pressed_start = false;
+            *point_pressed = false;
+            *pressed_root = false;
+        }
+
+        *self
+    }
+}
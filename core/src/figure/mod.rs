@@ -0,0 +1,539 @@
+pub mod edge;
+use self::edge::{Edge, EdgeDrawOption, EdgeFormat};
+use crate::{
+    input::Pointer,
+    log,
+    maths::{lerp_angle, vector2_rotate, Vec2},
+};
+use std::collections::HashMap;
+
+/// Largest angle, in radians, between an edge and its edited copy
+const MATCH_ANGLE: f32 = 0.01;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FigMode {
+    CONSTRUCTOR = 1,
+    ANIMATION = 2,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Figure {
+    pub tree: Vec<Edge>,
+    pub mode: FigMode,
+    pub should_update: bool,
+    pub draw_option: EdgeDrawOption,
+    pub selected: Option<usize>,
+    pub tmp_edge: Option<Edge>,
+    pub pressed: bool,
+    pub presset_root: bool,
+}
+
+impl Figure {
+    pub fn new(tree: Vec<Edge>) -> Figure {
+        let mut figure = Figure {
+            tree,
+            presset_root: false,
+            selected: None,
+            mode: FigMode::CONSTRUCTOR,
+            draw_option: EdgeDrawOption::new(),
+            tmp_edge: None,
+            pressed: false,
+            should_update: true,
+        };
+        figure.sort();
+        figure
+    }
+
+    // 1. === Update ===
+    pub fn update(&mut self, pointer: &Pointer, start_position: Vec2) {
+        self.selected = None;
+
+        if self.should_update {
+            for i in 0..self.tree.len() {
+                let mut edge: Edge = self.tree[i];
+
+                let pressed_before = self.pressed;
+
+                self.tree[i] = edge.update(
+                    pointer,
+                    &self.tree,
+                    &mut self.pressed,
+                    &mut self.presset_root,
+                    start_position,
+                );
+
+                if pressed_before != self.pressed {
+                    self.selected = Some(i);
+                }
+            }
+        }
+    }
+
+    // 2. === Helper functions ===
+    pub fn get_children(&self, index: isize) -> Vec<usize> {
+        self.tree
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.parent == index)
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>()
+    }
+
+    fn sort(&mut self) {
+        // Start from -1 as root parent to search.
+        let indexed = self.indexed_tree(-1);
+        let mut changed_indexes = HashMap::new();
+
+        self.tree = indexed
+            .iter()
+            .enumerate()
+            .map(|(i, e)| {
+                let mut edge = self.tree[*e];
+
+                if *e != i {
+                    changed_indexes.insert(*e, i as isize);
+                }
+
+                let parent = changed_indexes.get(&(edge.parent as usize));
+
+                // Update parent index
+                if let Some(parent) = parent {
+                    edge.parent = *parent;
+                }
+
+                edge
+            })
+            .collect();
+    }
+
+    /// Recursive function that re-map and organize the tree in vector space.
+    /// * TODO: I think the children function can be optimized with something like
+    ///   a Rc<RefCell<Edge>> and mantain a vector view of the edges. Will cost much time
+    ///   to refactor.
+    ///
+    fn indexed_tree(&self, index: isize) -> Vec<usize> {
+        let mut children = self.get_children(index);
+        let mut sector = children.to_vec();
+
+        children.reverse();
+        for child in children {
+            sector = [sector, self.indexed_tree(child as isize)].concat();
+        }
+
+        sector
+    }
+
+    /// Get edge at given index
+    pub fn get(&self, index: usize) -> &Edge {
+        &self.tree[index]
+    }
+
+    /// Get mutable edge at given index
+    pub fn get_mut(&mut self, index: usize) -> &mut Edge {
+        &mut self.tree[index]
+    }
+
+    /// Unselect parent and children edges
+    pub fn clear_edge_and_children(&mut self, index: usize) {
+        let parent = self.get(index).parent;
+        self.get_mut(index).pressed_start = false;
+        self.get_mut(index).pressed_end = false;
+        self.get_mut(index).moved_angle = 0.0;
+
+        for index in self.get_children(parent) {
+            let child = self.get_mut(index);
+            child.pressed_start = false;
+            child.pressed_end = false;
+        }
+    }
+
+    /// Copy edge to temporary space
+    pub fn copy_tmp(&mut self, index: usize) {
+        let mut edge = self.tree[index];
+        std::mem::swap(&mut edge.start, &mut edge.end);
+        edge.update_angle();
+        edge.moved_angle = 0.0;
+
+        self.tmp_edge = Some(edge);
+    }
+
+    /// Map and generate a static figure state
+    pub fn scan(&self) -> HashMap<usize, (Vec2, Vec2)> {
+        let mut compare_map = HashMap::new();
+
+        for (index, edge) in self.tree.iter().enumerate() {
+            compare_map.insert(index, (edge.start, edge.end));
+        }
+
+        compare_map
+    }
+
+    /// Load static state into Figure
+    pub fn load_state(&mut self, diff: HashMap<usize, (Vec2, Vec2)>) {
+        for (index, vertex) in diff.iter() {
+            let edge = &mut self.tree[*index];
+            edge.start = vertex.0;
+            edge.end = vertex.1;
+            edge.update_angle();
+        }
+    }
+
+    /// Replace the whole tree, dropping any pending interaction
+    pub fn restore(&mut self, tree: Vec<Edge>) {
+        self.tree = tree;
+        self.release();
+    }
+
+    /// Drop any pending interaction, keeping the tree as is
+    pub fn release(&mut self) {
+        self.tmp_edge = None;
+        self.selected = None;
+        self.pressed = false;
+        self.presset_root = false;
+        self.should_update = true;
+
+        for edge in &mut self.tree {
+            edge.pressed_start = false;
+            edge.pressed_end = false;
+            edge.moved = false;
+            edge.moved_angle = 0.0;
+        }
+    }
+
+    /// Generate an in-between static state of two figure states.
+    /// Each edge is rotated around its parent joint instead of moving
+    /// its points straight, so the limbs keep their length.
+    pub fn interpolate_state(
+        &self,
+        from: &HashMap<usize, (Vec2, Vec2)>,
+        to: &HashMap<usize, (Vec2, Vec2)>,
+        amount: f32,
+    ) -> HashMap<usize, (Vec2, Vec2)> {
+        let mut state: HashMap<usize, (Vec2, Vec2)> = HashMap::new();
+
+        // Walk the tree from roots, so parents are always solved before children.
+        for index in self.indexed_tree(-1) {
+            let edge = &self.tree[index];
+            let (from_start, from_end) = *from.get(&index).unwrap_or(&(edge.start, edge.end));
+            let (to_start, to_end) = *to.get(&index).unwrap_or(&(from_start, from_end));
+
+            let start = match state.get(&(edge.parent as usize)) {
+                Some((_, parent_end)) if edge.parent >= 0 => *parent_end,
+                _ => from_start.lerp(to_start, amount),
+            };
+
+            let from_width = from_start.distance_to(from_end);
+            let width = from_width + (to_start.distance_to(to_end) - from_width) * amount;
+            let angle = lerp_angle(
+                from_end.angle_to(from_start),
+                to_end.angle_to(to_start),
+                amount,
+            );
+
+            state.insert(index, (start, vector2_rotate(width, angle).add(start)));
+        }
+
+        state
+    }
+
+    /// Edge of this figure each edge of an edited copy comes from, if any.
+    /// Edges are matched from the roots by pointing the same way under the same
+    /// matched ancestor, so inserted, divided and deleted edges don't shift the others.
+    pub fn match_edges(&self, edited: &Figure) -> Vec<Option<usize>> {
+        let mut matches: Vec<Option<usize>> = vec![None; edited.tree.len()];
+        let mut used = vec![false; self.tree.len()];
+
+        // Closest matched ancestor of an edge, -1 for the figure root
+        let anchor = |tree: &[Edge], mut parent: isize, matched: &dyn Fn(usize) -> bool| {
+            while parent >= 0 && !matched(parent as usize) {
+                parent = tree[parent as usize].parent;
+            }
+            parent
+        };
+
+        for index in edited.indexed_tree(-1) {
+            let edge = &edited.tree[index];
+            let angle = edge.end.angle_to(edge.start);
+            let ancestor = match anchor(&edited.tree, edge.parent, &|i| matches[i].is_some()) {
+                -1 => -1,
+                ancestor => matches[ancestor as usize].unwrap() as isize,
+            };
+
+            let found = (0..self.tree.len())
+                .filter(|other| !used[*other])
+                .filter(|other| {
+                    anchor(&self.tree, self.tree[*other].parent, &|i| used[i]) == ancestor
+                })
+                .map(|other| {
+                    let other_edge = &self.tree[other];
+                    let turn = other_edge.end.angle_to(other_edge.start) - angle;
+                    (other, turn.sin().atan2(turn.cos()).abs())
+                })
+                .filter(|(_, turn)| *turn < MATCH_ANGLE)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(other, _)| other);
+
+            if let Some(other) = found {
+                used[other] = true;
+            }
+
+            matches[index] = found;
+        }
+
+        matches
+    }
+
+    /// Pose an edited copy of this figure like `state` poses this figure.
+    /// Matched edges point the same way as in `state`, other edges turn
+    /// as much as their parent did.
+    pub fn remap_state(
+        &self,
+        edited: &Figure,
+        matches: &[Option<usize>],
+        state: &HashMap<usize, (Vec2, Vec2)>,
+    ) -> HashMap<usize, (Vec2, Vec2)> {
+        let mut remapped: HashMap<usize, (Vec2, Vec2)> = HashMap::new();
+        let origin = match (state.get(&0), edited.tree.first()) {
+            (Some((start, _)), _) => *start,
+            (None, Some(edge)) => edge.start,
+            (None, None) => return remapped,
+        };
+
+        // Walk the tree from roots, so parents are always solved before children.
+        for index in edited.indexed_tree(-1) {
+            let edge = &edited.tree[index];
+            let parent = match remapped.get(&(edge.parent as usize)) {
+                Some(pose) if edge.parent >= 0 => Some(*pose),
+                _ => None,
+            };
+            let start = parent.map(|(_, end)| end).unwrap_or(origin);
+
+            let angle = match matches[index].and_then(|other| state.get(&other)) {
+                Some((from, to)) => to.angle_to(*from),
+                None => {
+                    let turn = match parent {
+                        Some((parent_start, parent_end)) => {
+                            let before = &edited.tree[edge.parent as usize];
+                            parent_end.angle_to(parent_start) - before.end.angle_to(before.start)
+                        }
+                        None => 0.0,
+                    };
+
+                    edge.end.angle_to(edge.start) + turn
+                }
+            };
+
+            let width = edge.start.distance_to(edge.end);
+            remapped.insert(index, (start, vector2_rotate(width, angle).add(start)));
+        }
+
+        remapped
+    }
+
+    pub fn center_to(&mut self, center: Vec2) {
+        let diff = self.tree[0].start.sub(center);
+
+        for edge in &mut self.tree {
+            edge.start = edge.start.sub(diff);
+            edge.end = edge.end.sub(diff);
+        }
+    }
+
+    /// x, y, width and height of the area covered by the edges, circles included
+    pub fn rect(&self) -> (f32, f32, f32, f32) {
+        if self.tree.is_empty() {
+            return (0.0, 0.0, 0.0, 0.0);
+        }
+
+        let mut min = Vec2::new(f32::MAX, f32::MAX);
+        let mut max = Vec2::new(f32::MIN, f32::MIN);
+
+        for edge in &self.tree {
+            let points = match edge.format {
+                EdgeFormat::LINE => [edge.start, edge.end],
+                EdgeFormat::CIRCLE => {
+                    let radius = edge.width / 2.0;
+                    let center =
+                        vector2_rotate(radius, edge.start.angle_to(edge.end)).add(edge.end);
+                    let corner = Vec2::new(radius, radius);
+                    [center.sub(corner), center.add(corner)]
+                }
+            };
+
+            for point in points {
+                min = Vec2::new(min.x.min(point.x), min.y.min(point.y));
+                max = Vec2::new(max.x.max(point.x), max.y.max(point.y));
+            }
+        }
+
+        (min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+
+    // 3. === Controllers ===
+    pub fn toggle_type(&mut self, index: usize) {
+        match self.tree[index].format {
+            EdgeFormat::CIRCLE => {
+                self.tree[index].format = EdgeFormat::LINE;
+            }
+            EdgeFormat::LINE => {
+                self.tree[index].format = EdgeFormat::CIRCLE;
+            }
+        };
+    }
+
+    pub fn divide(&mut self, index: usize) {
+        let grandfather = self.tree[index];
+
+        if grandfather.format == EdgeFormat::LINE {
+            let children = self.get_children(index as isize);
+
+            let start = grandfather.start.lerp(grandfather.end, 0.5);
+
+            let mut parent = Edge::new(start, grandfather.end, index as isize, 1);
+            parent.color = grandfather.color;
+            parent.thickness = grandfather.thickness;
+
+            let idx = self.tree.len();
+            self.tree.push(parent);
+
+            // Update grandfather
+            self.tree[index].end = start;
+            self.tree[index].width = grandfather.start.distance_to(start);
+
+            // Update cildren
+            for child in children {
+                self.tree[child].parent = idx as isize;
+            }
+
+            self.sort();
+        }
+    }
+
+    pub fn insert(&mut self, edge: Edge) {
+        self.tree.push(edge);
+        self.sort();
+    }
+
+    /// Delete edge on given index and update edges indexing parents
+    pub fn delete(&mut self, index: usize) {
+        log!("Deleting edge on index: {}", index);
+        let edge = self.tree[index];
+
+        log!("Index, Parent: {} , {}", index, edge.parent);
+        log!("Children: {:?}", self.get_children(index as isize));
+
+        for child in self.get_children(index as isize) {
+            self.tree[child].parent = edge.parent;
+
+            if edge.parent == -1 {
+                let brother = self.tree.iter().find(|e| e.parent == -1).unwrap();
+
+                self.tree[child].start = brother.start;
+                self.tree[child].end = self.tree[child].get_real_end();
+            }
+        }
+
+        log!("Len: {}", self.tree.len());
+        log!("Tree: {:?}", self.indexed_tree(-1));
+
+        let mut changed_indexes = HashMap::new();
+
+        for i in index..self.tree.len() {
+            changed_indexes.insert(i, i as isize - 1);
+
+            if let Some(p) = changed_indexes.get(&i) {
+                log!("Changed index: {} => {}", i, p);
+                for child in self.get_children(i as isize) {
+                    self.tree[child].parent = *p;
+                }
+            }
+        }
+
+        self.tree.remove(index);
+
+        log!("Len: {}", self.tree.len());
+        log!("Tree: {:?}", self.indexed_tree(-1));
+        log!("Changed Indexes: {:?}", changed_indexes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drag_edge_end_with_pointer() {
+        let mut figure = Figure::new(vec![Edge::new(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(0.0, -100.0),
+            -1,
+            1,
+        )]);
+
+        figure.update(&Pointer::new(Vec2::new(0.0, -100.0), true), Vec2::zero());
+        figure.update(&Pointer::new(Vec2::new(100.0, 0.0), true), Vec2::zero());
+        assert_eq!(figure.tree[0].end, Vec2::new(100.0, 0.0));
+
+        figure.update(&Pointer::new(Vec2::new(100.0, 0.0), false), Vec2::zero());
+        assert!(!figure.tree[0].pressed_end);
+        assert!(!figure.pressed);
+    }
+
+    #[test]
+    fn drag_edge_end_on_zoomed_out_view() {
+        let mut figure = Figure::new(vec![Edge::new(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(0.0, -100.0),
+            -1,
+            1,
+        )]);
+        let origin = Vec2::new(50.0, 50.0);
+
+        // At 25% the end point is drawn 25 pixels above the origin, a pixel off still grabs it
+        figure.update(
+            &Pointer::new(Vec2::new(51.0, 25.0), true).zoomed(0.25),
+            origin,
+        );
+        assert!(figure.tree[0].pressed_end);
+
+        figure.update(
+            &Pointer::new(Vec2::new(75.0, 50.0), true).zoomed(0.25),
+            origin,
+        );
+        assert_eq!(figure.tree[0].end, Vec2::new(100.0, 0.0));
+        assert_eq!(figure.rect(), (0.0, 0.0, 100.0, 0.0));
+    }
+
+    #[test]
+    fn poses_follow_edited_figure() {
+        let figure = Figure::new(vec![
+            Edge::new(Vec2::new(0.0, 0.0), Vec2::new(0.0, -100.0), -1, 1),
+            Edge::new(Vec2::new(0.0, -100.0), Vec2::new(50.0, -100.0), 0, 1),
+        ]);
+
+        // Frame where the arm is raised
+        let mut state = figure.scan();
+        state.insert(1, (Vec2::new(0.0, -100.0), Vec2::new(0.0, -150.0)));
+
+        // Splitting the body in two shifts the arm index
+        let mut edited = figure.clone();
+        edited.divide(0);
+        let arm = edited
+            .tree
+            .iter()
+            .position(|edge| edge.end.x == 50.0)
+            .unwrap();
+
+        let matches = figure.match_edges(&edited);
+        assert_eq!(matches[0], Some(0));
+        assert_eq!(matches[arm], Some(1));
+        assert_eq!(matches.iter().filter(|m| m.is_none()).count(), 1);
+
+        let remapped = figure.remap_state(&edited, &matches, &state);
+        assert_eq!(remapped.len(), 3);
+        assert_eq!(
+            remapped[&arm],
+            (Vec2::new(0.0, -100.0), Vec2::new(0.0, -150.0))
+        );
+    }
+}
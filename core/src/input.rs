@@ -0,0 +1,154 @@
+use crate::maths::Vec2;
+
+/// Smallest and largest canvas magnification of a viewport
+pub const MIN_ZOOM: f32 = 0.1;
+pub const MAX_ZOOM: f32 = 10.0;
+
+/// Pointer state used to drag figure points, so figures
+/// can be posed by any front-end or by tests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pointer {
+    pub position: Vec2,
+    /// Main button is held down
+    pub down: bool,
+    /// Screen pixels per canvas pixel of the view the pointer is over
+    pub zoom: f32,
+}
+
+/// Pan and zoom of a canvas shown inside a screen area
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// Canvas top left corner relative to the area top left corner, in screen pixels
+    pub offset: Vec2,
+    /// Screen pixels per canvas pixel
+    pub zoom: f32,
+}
+
+impl Default for Pointer {
+    fn default() -> Pointer {
+        Pointer::new(Vec2::zero(), false)
+    }
+}
+
+impl Pointer {
+    pub fn new(position: Vec2, down: bool) -> Pointer {
+        Pointer {
+            position,
+            down,
+            zoom: 1.0,
+        }
+    }
+
+    /// Same pointer over a view with given zoom
+    pub fn zoomed(self, zoom: f32) -> Pointer {
+        Pointer { zoom, ..self }
+    }
+
+    /// Read mouse position and left button from raylib
+    #[cfg(feature = "raylib")]
+    pub fn from_handle(handle: &raylib::prelude::RaylibHandle) -> Pointer {
+        Pointer::new(
+            handle.get_mouse_position().into(),
+            handle.is_mouse_button_down(raylib::prelude::MouseButton::MOUSE_BUTTON_LEFT),
+        )
+    }
+}
+
+impl Viewport {
+    pub fn new(offset: Vec2) -> Viewport {
+        Viewport { offset, zoom: 1.0 }
+    }
+
+    /// Viewport showing the canvas center on the area center, at 100%
+    pub fn centered(canvas: (u32, u32), area: (f32, f32)) -> Viewport {
+        Viewport::new(Vec2::new(
+            (area.0 - canvas.0 as f32) / 2.0,
+            (area.1 - canvas.1 as f32) / 2.0,
+        ))
+    }
+
+    /// Canvas point under an area position
+    pub fn to_canvas(&self, position: Vec2) -> Vec2 {
+        position.sub(self.offset).scale_by(1.0 / self.zoom)
+    }
+
+    /// Area position of a canvas point
+    pub fn to_area(&self, point: Vec2) -> Vec2 {
+        point.scale_by(self.zoom).add(self.offset)
+    }
+
+    /// Change zoom keeping the canvas point under an area position in place
+    pub fn zoom_at(&mut self, position: Vec2, zoom: f32) {
+        let point = self.to_canvas(position);
+
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        self.offset = position.sub(point.scale_by(self.zoom));
+    }
+
+    /// Show a canvas rectangle, given as x, y, width and height,
+    /// as large as possible on the area center with a margin around it
+    pub fn fit(&mut self, rect: (f32, f32, f32, f32), area: (f32, f32), margin: f32) {
+        let (x, y, width, height) = rect;
+        let zoom = ((area.0 - margin * 2.0) / width.max(1.0))
+            .min((area.1 - margin * 2.0) / height.max(1.0));
+        let center = Vec2::new(x + width / 2.0, y + height / 2.0);
+
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        self.offset = Vec2::new(area.0 / 2.0, area.1 / 2.0).sub(center.scale_by(self.zoom));
+    }
+
+    /// Keep the canvas covering the area, or centered on it when it is smaller
+    pub fn clamp_to(&mut self, canvas: (u32, u32), area: (f32, f32)) {
+        let axis = |offset: f32, canvas: u32, area: f32| {
+            let size = canvas as f32 * self.zoom;
+
+            if size <= area {
+                (area - size) / 2.0
+            } else {
+                offset.clamp(area - size, 0.0)
+            }
+        };
+
+        self.offset = Vec2::new(
+            axis(self.offset.x, canvas.0, area.0),
+            axis(self.offset.y, canvas.1, area.1),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewport_zooms_around_the_cursor() {
+        let mut view = Viewport::centered((1000, 1000), (200.0, 100.0));
+        assert_eq!(
+            view.to_canvas(Vec2::new(100.0, 50.0)),
+            Vec2::new(500.0, 500.0)
+        );
+
+        let cursor = Vec2::new(20.0, 80.0);
+        let under = view.to_canvas(cursor);
+        view.zoom_at(cursor, 4.0);
+        assert_eq!(view.zoom, 4.0);
+        assert!(view.to_canvas(cursor).distance_to(under) < 0.001);
+
+        view.zoom_at(cursor, 100.0);
+        assert_eq!(view.zoom, MAX_ZOOM);
+
+        // A 100x50 rectangle fills the area, less its margin
+        view.fit((300.0, 300.0, 100.0, 50.0), (200.0, 100.0), 10.0);
+        assert_eq!(view.zoom, 1.6);
+        assert!(
+            view.to_area(Vec2::new(350.0, 325.0))
+                .distance_to(Vec2::new(100.0, 50.0))
+                < 0.001
+        );
+
+        // Zoomed out, the whole canvas is centered on the area
+        view.zoom = 0.1;
+        view.clamp_to((1000, 1000), (200.0, 100.0));
+        assert_eq!(view.offset, Vec2::new(50.0, 0.0));
+    }
+}
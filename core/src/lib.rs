@@ -0,0 +1,12 @@
+//! Vetor figure and animation logic, without any window or renderer.
+
+// Enum variants are written in upper case, like raylib constants
+#![allow(non_camel_case_types)]
+
+pub mod animation;
+pub mod archives;
+pub mod export;
+pub mod figure;
+pub mod input;
+pub mod maths;
+mod util;
@@ -0,0 +1,311 @@
+use std::{
+    f32::consts::{PI, TAU},
+    fmt::Display,
+    ops::{Add, Neg, Sub},
+    str::FromStr,
+};
+
+/// Two dimensional vector, used by figures and files.
+/// Enable the `raylib` feature to convert from and into raylib vectors.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    pub const fn new(x: f32, y: f32) -> Vec2 {
+        Vec2 { x, y }
+    }
+
+    pub const fn zero() -> Vec2 {
+        Vec2 { x: 0.0, y: 0.0 }
+    }
+
+    pub fn add(&self, v: Vec2) -> Vec2 {
+        Vec2::new(self.x + v.x, self.y + v.y)
+    }
+
+    pub fn sub(&self, v: Vec2) -> Vec2 {
+        Vec2::new(self.x - v.x, self.y - v.y)
+    }
+
+    pub fn scale_by(&self, scale: f32) -> Vec2 {
+        Vec2::new(self.x * scale, self.y * scale)
+    }
+
+    pub fn length(&self) -> f32 {
+        (self.x * self.x + self.y * self.y).sqrt()
+    }
+
+    pub fn distance_to(&self, v: Vec2) -> f32 {
+        v.sub(*self).length()
+    }
+
+    /// Angle (in radians) of the vector pointing from self to `v`
+    pub fn angle_to(&self, v: Vec2) -> f32 {
+        (v.y - self.y).atan2(v.x - self.x)
+    }
+
+    pub fn lerp(&self, v: Vec2, amount: f32) -> Vec2 {
+        Vec2::new(
+            self.x + amount * (v.x - self.x),
+            self.y + amount * (v.y - self.y),
+        )
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, v: Vec2) -> Vec2 {
+        Vec2::add(&self, v)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, v: Vec2) -> Vec2 {
+        Vec2::sub(&self, v)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+#[cfg(feature = "raylib")]
+impl From<raylib::prelude::Vector2> for Vec2 {
+    fn from(v: raylib::prelude::Vector2) -> Vec2 {
+        Vec2::new(v.x, v.y)
+    }
+}
+
+#[cfg(feature = "raylib")]
+impl From<Vec2> for raylib::prelude::Vector2 {
+    fn from(v: Vec2) -> raylib::prelude::Vector2 {
+        raylib::prelude::Vector2::new(v.x, v.y)
+    }
+}
+
+#[cfg(feature = "raylib")]
+impl From<Vec2> for raylib::ffi::Vector2 {
+    fn from(v: Vec2) -> raylib::ffi::Vector2 {
+        raylib::ffi::Vector2 { x: v.x, y: v.y }
+    }
+}
+
+/// RGBA color, used by figures to tint edges
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Rgba {
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Rgba {
+        Rgba { r, g, b, a }
+    }
+}
+
+/// Written as hex `#rrggbb`, with alpha `#rrggbbaa` when transparent
+impl Display for Rgba {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)?;
+
+        if self.a != 255 {
+            write!(f, "{:02x}", self.a)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Hex colors, with optional `#` and alpha: `#ffffff`, `00000000`
+impl FromStr for Rgba {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let hex = value.trim().trim_start_matches('#');
+
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+            return Err(());
+        }
+
+        let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).map_err(|_| ());
+        let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+
+        Ok(Rgba::new(channel(0)?, channel(2)?, channel(4)?, alpha))
+    }
+}
+
+#[cfg(feature = "raylib")]
+impl From<raylib::prelude::Color> for Rgba {
+    fn from(c: raylib::prelude::Color) -> Rgba {
+        Rgba::new(c.r, c.g, c.b, c.a)
+    }
+}
+
+#[cfg(feature = "raylib")]
+impl From<Rgba> for raylib::prelude::Color {
+    fn from(c: Rgba) -> raylib::prelude::Color {
+        raylib::prelude::Color::new(c.r, c.g, c.b, c.a)
+    }
+}
+
+pub fn vector2_rotate(length: f32, angle: f32) -> Vec2 {
+    let cs = (angle.cos() * 100.0).round() / 100.0;
+    let sn = (angle.sin() * 100.0).round() / 100.0;
+
+    // Multiply by -1 because coordinate rotation is reversed.
+    let x = (-length * cs).round();
+    let y = (-length * sn).round();
+
+    Vec2::new(x, y)
+}
+
+pub fn check_collision_point_circle(point: Vec2, center: Vec2, radius: f32) -> bool {
+    point.distance_to(center) <= radius
+}
+
+/// Interpolate between two angles (in radians) through the shortest arc.
+pub fn lerp_angle(from: f32, to: f32, amount: f32) -> f32 {
+    let mut diff = (to - from) % TAU;
+
+    if diff > PI {
+        diff -= TAU;
+    } else if diff < -PI {
+        diff += TAU;
+    }
+
+    from + diff * amount
+}
+
+/// One dimension of a cubic bezier curve starting at 0 and ending at 1.
+pub fn cubic_bezier(p1: f32, p2: f32, t: f32) -> f32 {
+    let inv = 1.0 - t;
+    3.0 * inv * inv * t * p1 + 3.0 * inv * t * t * p2 + t * t * t
+}
+
+/// Timing curve used to generate motion in between two poses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    LINEAR,
+    EASE_IN,
+    EASE_OUT,
+    EASE_IN_OUT,
+    /// Control points (x1, y1, x2, y2) like the css cubic-bezier function.
+    BEZIER(f32, f32, f32, f32),
+}
+
+impl Easing {
+    /// Map linear progress (0 to 1) into eased progress.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match *self {
+            Easing::LINEAR => t,
+            Easing::EASE_IN => t * t * t,
+            Easing::EASE_OUT => 1.0 - (1.0 - t).powi(3),
+            Easing::EASE_IN_OUT => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::BEZIER(x1, y1, x2, y2) => {
+                // Search curve parameter where x reaches the progress.
+                let (mut low, mut high, mut s) = (0.0, 1.0, t);
+
+                for _ in 0..24 {
+                    let x = cubic_bezier(x1, x2, s);
+
+                    if (x - t).abs() < 1e-5 {
+                        break;
+                    } else if x < t {
+                        low = s;
+                    } else {
+                        high = s;
+                    }
+
+                    s = (low + high) / 2.0;
+                }
+
+                cubic_bezier(y1, y2, s)
+            }
+        }
+    }
+}
+
+impl From<i32> for Easing {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Easing::EASE_IN,
+            2 => Easing::EASE_OUT,
+            3 => Easing::EASE_IN_OUT,
+            4 => Easing::BEZIER(0.25, 0.1, 0.25, 1.0),
+            _ => Easing::LINEAR,
+        }
+    }
+}
+
+impl From<Easing> for i32 {
+    fn from(easing: Easing) -> i32 {
+        match easing {
+            Easing::LINEAR => 0,
+            Easing::EASE_IN => 1,
+            Easing::EASE_OUT => 2,
+            Easing::EASE_IN_OUT => 3,
+            Easing::BEZIER(..) => 4,
+        }
+    }
+}
+
+impl Display for Easing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Easing::LINEAR => write!(f, "linear"),
+            Easing::EASE_IN => write!(f, "ease-in"),
+            Easing::EASE_OUT => write!(f, "ease-out"),
+            Easing::EASE_IN_OUT => write!(f, "ease-in-out"),
+            Easing::BEZIER(x1, y1, x2, y2) => write!(f, "bezier({},{},{},{})", x1, y1, x2, y2),
+        }
+    }
+}
+
+impl FromStr for Easing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Easing::LINEAR),
+            "ease-in" => Ok(Easing::EASE_IN),
+            "ease-out" => Ok(Easing::EASE_OUT),
+            "ease-in-out" => Ok(Easing::EASE_IN_OUT),
+            _ => {
+                let points = s
+                    .strip_prefix("bezier(")
+                    .and_then(|s| s.strip_suffix(")"))
+                    .ok_or(format!("Unknown easing: {}", s))?
+                    .split(',')
+                    .map(|p| p.trim().parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| format!("Invalid bezier points: {}", s))?;
+
+                if points.len() != 4 {
+                    return Err(format!("Bezier easing needs 4 points: {}", s));
+                }
+
+                Ok(Easing::BEZIER(points[0], points[1], points[2], points[3]))
+            }
+        }
+    }
+}
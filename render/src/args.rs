@@ -0,0 +1,163 @@
+use std::fmt::Display;
+use vetor_core::maths::Rgba;
+
+pub const USAGE: &str = "\
+Usage: vetor-render <animation.var> (--png <dir> | --video <file>) [options] [-- <ffmpeg args>]
+
+Outputs:
+    --png <dir>           Write frames as frame_0000.png, frame_0001.png, ... into dir
+    --video <file>        Pipe frames into ffmpeg, encoding to file
+
+Options:
+    --fps <n>             Video framerate, defaults to the animation framerate
+    --size <WxH>          Output size, the camera is scaled to fit. Defaults to the camera size
+    --background <color>  Hex RRGGBB or RRGGBBAA color. Defaults to f5f5f5
+    -h, --help            Show this message
+
+Arguments after -- are passed to ffmpeg before the output file.";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Output {
+    PNG(String),
+    VIDEO(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub input: String,
+    pub output: Output,
+    pub fps: Option<f32>,
+    pub size: Option<(u32, u32)>,
+    pub background: Rgba,
+    pub ffmpeg_args: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgsError {
+    HELP,
+    INVALID(String),
+}
+
+impl Display for ArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgsError::HELP => write!(f, "{}", USAGE),
+            ArgsError::INVALID(message) => write!(f, "{}\n\n{}", message, USAGE),
+        }
+    }
+}
+
+impl Options {
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options, ArgsError> {
+        let mut args = args.into_iter();
+        let mut input = None;
+        let mut output = None;
+        let mut fps = None;
+        let mut size = None;
+        let mut background = Rgba::new(245, 245, 245, 255);
+        let mut ffmpeg_args = vec![];
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| ArgsError::INVALID(format!("missing value for {}", name)))
+            };
+
+            match arg.as_str() {
+                "-h" | "--help" => return Err(ArgsError::HELP),
+                "--png" => output = Some(Output::PNG(value("--png")?)),
+                "--video" => output = Some(Output::VIDEO(value("--video")?)),
+                "--fps" => {
+                    let fps_str = value("--fps")?;
+                    fps = match fps_str.parse::<f32>() {
+                        Ok(value) if value > 0.0 => Some(value),
+                        _ => return Err(invalid("fps", &fps_str)),
+                    };
+                }
+                "--size" => {
+                    let size_str = value("--size")?;
+                    size = Some(parse_size(&size_str).ok_or_else(|| invalid("size", &size_str))?);
+                }
+                "--background" => {
+                    let color_str = value("--background")?;
+                    background = color_str
+                        .parse::<Rgba>()
+                        .map_err(|_| invalid("color", &color_str))?;
+                }
+                "--" => {
+                    ffmpeg_args = args.by_ref().collect();
+                }
+                _ if arg.starts_with("-") => {
+                    return Err(ArgsError::INVALID(format!("unknown option {}", arg)))
+                }
+                _ if input.is_none() => input = Some(arg),
+                _ => return Err(ArgsError::INVALID(format!("unexpected argument {}", arg))),
+            }
+        }
+
+        Ok(Options {
+            input: input.ok_or_else(|| ArgsError::INVALID("missing animation file".into()))?,
+            output: output
+                .ok_or_else(|| ArgsError::INVALID("missing --png or --video output".into()))?,
+            fps,
+            size,
+            background,
+            ffmpeg_args,
+        })
+    }
+}
+
+fn invalid(name: &str, value: &str) -> ArgsError {
+    ArgsError::INVALID(format!("invalid {} {}", name, value))
+}
+
+/// Parse `WxH` sizes, as `1920x1080`
+fn parse_size(value: &str) -> Option<(u32, u32)> {
+    let (width, height) = value.split_once(['x', 'X'])?;
+    let size = (width.parse::<u32>().ok()?, height.parse::<u32>().ok()?);
+
+    if size.0 == 0 || size.1 == 0 {
+        return None;
+    }
+
+    Some(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, ArgsError> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn options_are_parsed() {
+        let options = parse(&[
+            "walk.var",
+            "--video",
+            "walk.mp4",
+            "--fps",
+            "12",
+            "--size",
+            "640x360",
+            "--background",
+            "#00000000",
+            "--",
+            "-pix_fmt",
+            "yuv420p",
+        ])
+        .unwrap();
+
+        assert_eq!(options.input, "walk.var");
+        assert_eq!(options.output, Output::VIDEO("walk.mp4".into()));
+        assert_eq!(options.fps, Some(12.0));
+        assert_eq!(options.size, Some((640, 360)));
+        assert_eq!(options.background, Rgba::new(0, 0, 0, 0));
+        assert_eq!(options.ffmpeg_args, vec!["-pix_fmt", "yuv420p"]);
+
+        assert!(parse(&["walk.var"]).is_err());
+        assert!(parse(&["walk.var", "--png", "out", "--size", "0x10"]).is_err());
+        assert!(parse(&["walk.var", "--png", "out", "--background", "red"]).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! Render Vetor animations without a window, for build servers and scripts.
+//!
+//! ```text
+//! vetor-render walk.var --png frames/
+//! vetor-render walk.var --video walk.mp4 --fps 12 --size 1920x1080 -- -pix_fmt yuv420p
+//! ```
+
+// Enum variants are written in upper case, like raylib constants
+#![allow(clippy::upper_case_acronyms)]
+
+mod args;
+mod raster;
+
+use args::{ArgsError, Options, Output};
+use raster::{Canvas, View};
+use std::{
+    env,
+    error::Error,
+    fs,
+    io::Write,
+    path::Path,
+    process::{self, Command, Stdio},
+};
+use vetor_core::{
+    animation::{camera::Shot, AnimationData},
+    archives,
+    export::png,
+    maths::Rgba,
+};
+
+fn main() {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(ArgsError::HELP) => {
+            println!("{}", ArgsError::HELP);
+            return;
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(2);
+        }
+    };
+
+    if let Err(error) = render(&options) {
+        eprintln!("vetor-render: {}", error);
+        process::exit(1);
+    }
+}
+
+fn render(options: &Options) -> Result<(), Box<dyn Error>> {
+    let (raw, _) = archives::read(&options.input)?;
+    let animation = archives::raw_to_animation(&raw)?;
+
+    let (width, height) = options.size.unwrap_or(animation.header.camera.size);
+    let fps = options.fps.unwrap_or(animation.header.fps);
+    let shots = animation.shots();
+    let frames = shots.iter().enumerate().map(|(index, shot)| {
+        render_frame(&animation, index, *shot, width, height, options.background)
+    });
+
+    match &options.output {
+        Output::PNG(dir) => {
+            fs::create_dir_all(dir)?;
+
+            for (index, canvas) in frames.enumerate() {
+                let canvas = canvas?;
+                let path = Path::new(dir).join(format!("frame_{:04}.png", index));
+                png::write(&path.to_string_lossy(), width, height, &canvas.pixels)?;
+            }
+        }
+        Output::VIDEO(file) => {
+            let mut ffmpeg = Command::new("ffmpeg")
+                .args(["-y", "-loglevel", "error"])
+                .args(["-f", "rawvideo", "-pixel_format", "rgba"])
+                .args(["-video_size", &format!("{}x{}", width, height)])
+                .args(["-framerate", &fps.to_string(), "-i", "-"])
+                .args(&options.ffmpeg_args)
+                .arg(file)
+                .stdin(Stdio::piped())
+                .spawn()
+                .map_err(|error| format!("cannot spawn ffmpeg: {}", error))?;
+
+            let mut stdin = ffmpeg.stdin.take().unwrap();
+
+            // Videos have a constant framerate, held frames are repeated
+            for (frame, canvas) in animation.frames.iter().zip(frames) {
+                let canvas = canvas?;
+
+                for _ in 0..frame.hold {
+                    stdin.write_all(&canvas.pixels)?;
+                }
+            }
+
+            drop(stdin);
+
+            if !ffmpeg.wait()?.success() {
+                return Err("ffmpeg failed to encode the video".into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Draw every figure of a frame, as seen by the video camera on that frame
+fn render_frame(
+    animation: &AnimationData,
+    index: usize,
+    shot: Shot,
+    width: u32,
+    height: u32,
+    background: Rgba,
+) -> archives::error::Result<Canvas> {
+    let view = View::fit(&animation.header.camera, shot, width, height);
+    let mut canvas = Canvas::new(width, height)?;
+    canvas.clear(background);
+
+    for (global_index, state) in &animation.frames[index].states {
+        let mut figure = animation.figures[*global_index].clone();
+        figure.load_state(state.clone());
+        canvas.draw_figure(&figure, view, background);
+    }
+
+    Ok(canvas)
+}
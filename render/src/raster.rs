@@ -0,0 +1,328 @@
+use vetor_core::{
+    animation::camera::{Camera, Shot},
+    archives::error::{Result, VetorError},
+    figure::{
+        edge::{Edge, EdgeDrawMode, EdgeFormat},
+        Figure,
+    },
+    maths::*,
+};
+
+/// RGBA image drawn with signed distance shapes.
+/// Shape borders are antialiased by their pixel coverage.
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Maps animation coordinates, relative to the canvas center, into image pixels
+#[derive(Clone, Copy, Debug)]
+pub struct View {
+    /// Animation point drawn on the image center
+    pub center: Vec2,
+    /// Image center in pixels
+    pub origin: Vec2,
+    pub scale: f32,
+    /// Camera roll in degrees, clockwise
+    pub rotation: f32,
+}
+
+impl View {
+    /// Fit the camera rectangle centered in an image, keeping its aspect ratio,
+    /// then apply the frame shot
+    pub fn fit(camera: &Camera, shot: Shot, width: u32, height: u32) -> View {
+        let scale = (width as f32 / camera.size.0 as f32).min(height as f32 / camera.size.1 as f32);
+
+        View {
+            center: shot.center,
+            origin: Vec2::new(width as f32 / 2.0, height as f32 / 2.0),
+            scale: scale * shot.zoom,
+            rotation: shot.rotation,
+        }
+    }
+
+    pub fn project(&self, point: Vec2) -> Vec2 {
+        let shot = Shot {
+            center: self.center,
+            zoom: self.scale,
+            rotation: self.rotation,
+        };
+
+        shot.to_output(point).add(self.origin)
+    }
+}
+
+impl Canvas {
+    /// Blank image, fails when its pixels do not fit in memory addresses
+    pub fn new(width: u32, height: u32) -> Result<Canvas> {
+        let size = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|size| size.checked_mul(4))
+            .ok_or_else(|| VetorError::ENCODE {
+                message: format!("image of {}x{} pixels is too large", width, height),
+            })?;
+
+        Ok(Canvas {
+            width,
+            height,
+            pixels: vec![0; size],
+        })
+    }
+
+    pub fn clear(&mut self, color: Rgba) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    /// Draw figure edges in tree order, like the editor does
+    pub fn draw_figure(&mut self, figure: &Figure, view: View, background: Rgba) {
+        for edge in figure.tree.iter() {
+            self.draw_edge(edge, view, background);
+        }
+    }
+
+    /// Draw an edge with its own color and thickness
+    pub fn draw_edge(&mut self, edge: &Edge, view: View, background: Rgba) {
+        let start = view.project(edge.start);
+        let end = view.project(edge.end);
+        let half = edge.thickness / 2.0 * view.scale;
+        let color = edge.color;
+
+        match edge.format {
+            EdgeFormat::LINE => {
+                if edge.draw_mode == EdgeDrawMode::LINE_BORDER_FLAT {
+                    self.fill_flat_line(start, end, half, color);
+                } else {
+                    self.fill_capsule(start, end, half, color);
+                }
+            }
+            EdgeFormat::CIRCLE => {
+                let radius = edge.width / 2.0;
+                let center = view
+                    .project(vector2_rotate(radius, edge.start.angle_to(edge.end)).add(edge.end));
+                let radius = radius * view.scale;
+
+                // One shape, so antialiased pixels of the inner border are not covered twice
+                if edge.draw_mode == EdgeDrawMode::CIRCLE_FULL {
+                    self.fill_circle(center, radius + half, color);
+                    return;
+                }
+
+                self.fill_ring(center, radius - half, radius + half, color);
+
+                if edge.draw_mode == EdgeDrawMode::CIRCLE_CLEAN {
+                    self.clear_circle(center, radius - half, background);
+                }
+            }
+        }
+    }
+
+    /// Line with rounded borders
+    pub fn fill_capsule(&mut self, start: Vec2, end: Vec2, radius: f32, color: Rgba) {
+        let segment = end.sub(start);
+        let length = segment.length().max(f32::EPSILON);
+
+        self.fill(bounds(&[start, end], radius), color, |point| {
+            let relative = point.sub(start);
+            let t = ((relative.x * segment.x + relative.y * segment.y) / (length * length))
+                .clamp(0.0, 1.0);
+            point.distance_to(start.add(segment.scale_by(t))) - radius
+        });
+    }
+
+    /// Line with flat borders, extended by half thickness on both ends
+    pub fn fill_flat_line(&mut self, start: Vec2, end: Vec2, half: f32, color: Rgba) {
+        let length = start.distance_to(end);
+        let direction = if length > 0.0 {
+            end.sub(start).scale_by(1.0 / length)
+        } else {
+            Vec2::new(1.0, 0.0)
+        };
+        let center = start.lerp(end, 0.5);
+        let extent = (length / 2.0 + half, half);
+
+        self.fill(bounds(&[start, end], half * 2.0), color, |point| {
+            let relative = point.sub(center);
+            let u = (relative.x * direction.x + relative.y * direction.y).abs() - extent.0;
+            let v = (relative.y * direction.x - relative.x * direction.y).abs() - extent.1;
+            Vec2::new(u.max(0.0), v.max(0.0)).length() + u.max(v).min(0.0)
+        });
+    }
+
+    pub fn fill_ring(&mut self, center: Vec2, inner: f32, outer: f32, color: Rgba) {
+        let middle = (inner + outer) / 2.0;
+        let half = (outer - inner) / 2.0;
+
+        self.fill(bounds(&[center], outer), color, |point| {
+            (point.distance_to(center) - middle).abs() - half
+        });
+    }
+
+    pub fn fill_circle(&mut self, center: Vec2, radius: f32, color: Rgba) {
+        self.fill(bounds(&[center], radius), color, |point| {
+            point.distance_to(center) - radius
+        });
+    }
+
+    /// Replace the circle pixels by color, so edges below it are erased
+    /// even by a translucent background
+    pub fn clear_circle(&mut self, center: Vec2, radius: f32, color: Rgba) {
+        self.cover(
+            bounds(&[center], radius),
+            |point| point.distance_to(center) - radius,
+            |pixel, coverage| replace(pixel, color, coverage),
+        );
+    }
+
+    /// Blend color over every pixel of the bounds
+    fn fill<F>(&mut self, bounds: (Vec2, Vec2), color: Rgba, distance: F)
+    where
+        F: Fn(Vec2) -> f32,
+    {
+        self.cover(bounds, distance, |pixel, coverage| {
+            blend(pixel, color, coverage)
+        });
+    }
+
+    /// Paint every pixel of the bounds, weighted by how much
+    /// the pixel is covered by the shape signed distance.
+    fn cover<F, P>(&mut self, bounds: (Vec2, Vec2), distance: F, paint: P)
+    where
+        F: Fn(Vec2) -> f32,
+        P: Fn(&mut [u8], f32),
+    {
+        let (min, max) = bounds;
+        let x_range = (min.x.floor().max(0.0) as u32)..(max.x.ceil().min(self.width as f32) as u32);
+        let y_range =
+            (min.y.floor().max(0.0) as u32)..(max.y.ceil().min(self.height as f32) as u32);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                let point = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let coverage = (0.5 - distance(point)).clamp(0.0, 1.0);
+
+                if coverage > 0.0 {
+                    let index = (y as usize * self.width as usize + x as usize) * 4;
+                    paint(&mut self.pixels[index..index + 4], coverage);
+                }
+            }
+        }
+    }
+}
+
+/// Composite color over a RGBA pixel
+fn blend(pixel: &mut [u8], color: Rgba, coverage: f32) {
+    let source_alpha = color.a as f32 / 255.0 * coverage;
+    let target_alpha = pixel[3] as f32 / 255.0;
+    let alpha = source_alpha + target_alpha * (1.0 - source_alpha);
+
+    if alpha <= 0.0 {
+        return;
+    }
+
+    let channels = [color.r, color.g, color.b];
+
+    for channel in 0..3 {
+        let value = (channels[channel] as f32 * source_alpha
+            + pixel[channel] as f32 * target_alpha * (1.0 - source_alpha))
+            / alpha;
+        pixel[channel] = value.round() as u8;
+    }
+
+    pixel[3] = (alpha * 255.0).round() as u8;
+}
+
+/// Move a RGBA pixel towards color, alpha included
+fn replace(pixel: &mut [u8], color: Rgba, coverage: f32) {
+    let channels = [color.r, color.g, color.b, color.a];
+
+    for channel in 0..4 {
+        let value = pixel[channel] as f32 * (1.0 - coverage) + channels[channel] as f32 * coverage;
+        pixel[channel] = value.round() as u8;
+    }
+}
+
+/// Bounding box of points grown by a margin, plus one pixel for antialiasing
+fn bounds(points: &[Vec2], margin: f32) -> (Vec2, Vec2) {
+    let margin = margin + 1.0;
+    let mut min = Vec2::new(f32::MAX, f32::MAX);
+    let mut max = Vec2::new(f32::MIN, f32::MIN);
+
+    for point in points {
+        min = Vec2::new(min.x.min(point.x), min.y.min(point.y));
+        max = Vec2::new(max.x.max(point.x), max.y.max(point.y));
+    }
+
+    (
+        min.sub(Vec2::new(margin, margin)),
+        max.add(Vec2::new(margin, margin)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_edge_is_drawn_inside_the_camera() {
+        let white = Rgba::new(255, 255, 255, 255);
+        let camera = Camera::centered((100, 100));
+        let view = View::fit(&camera, camera.shot(), 50, 50);
+        let mut edge = Edge::new(Vec2::new(-20.0, 0.0), Vec2::new(20.0, 0.0), -1, 1);
+
+        let mut canvas = Canvas::new(50, 50).unwrap();
+        canvas.clear(white);
+        canvas.draw_edge(&edge, view, white);
+
+        let pixel = |canvas: &Canvas, x: u32, y: u32| {
+            let index = ((y * 50 + x) * 4) as usize;
+            canvas.pixels[index..index + 4].to_vec()
+        };
+
+        assert_eq!(pixel(&canvas, 25, 24), vec![0, 0, 0, 255]);
+        assert_eq!(pixel(&canvas, 25, 5), vec![255, 255, 255, 255]);
+        // Rounded border passes the edge end by half thickness
+        assert_eq!(pixel(&canvas, 37, 24), vec![0, 0, 0, 255]);
+
+        // Rolled a quarter turn and zoomed in, the line stands up to the image borders
+        let shot = Shot {
+            zoom: 2.0,
+            rotation: 90.0,
+            ..camera.shot()
+        };
+        canvas.clear(white);
+        canvas.draw_edge(&edge, View::fit(&camera, shot, 50, 50), white);
+
+        assert_eq!(pixel(&canvas, 24, 5), vec![0, 0, 0, 255]);
+        assert_eq!(pixel(&canvas, 5, 24), vec![255, 255, 255, 255]);
+
+        // Thin translucent red edges are blended over the background
+        edge.color = Rgba::new(255, 0, 0, 128);
+        edge.thickness = 4.0;
+        canvas.clear(white);
+        canvas.draw_edge(&edge, view, white);
+
+        assert_eq!(pixel(&canvas, 25, 24), vec![255, 127, 127, 255]);
+        assert_eq!(pixel(&canvas, 25, 22), vec![255, 255, 255, 255]);
+
+        // Full circles are blended once, even where the ring meets the inner circle
+        let mut circle = Edge::new(Vec2::new(10.0, 0.0), Vec2::new(-10.0, 0.0), -1, 2);
+        circle.draw_mode = EdgeDrawMode::CIRCLE_FULL;
+        circle.color = edge.color;
+        circle.thickness = 4.0;
+        canvas.clear(white);
+        canvas.draw_edge(&circle, view, white);
+
+        assert_eq!(pixel(&canvas, 25, 25), vec![255, 127, 127, 255]);
+        assert_eq!(pixel(&canvas, 28, 25), vec![255, 127, 127, 255]);
+
+        // Clean circles erase what is below them, even with a transparent background
+        canvas.clear(Rgba::new(0, 0, 0, 255));
+        canvas.clear_circle(Vec2::new(25.0, 25.0), 10.0, Rgba::new(0, 0, 0, 0));
+
+        assert_eq!(pixel(&canvas, 25, 25), vec![0, 0, 0, 0]);
+        assert_eq!(pixel(&canvas, 25, 5), vec![0, 0, 0, 255]);
+    }
+}
@@ -1,6 +1,5 @@
 #![allow(warnings)]
 
-mod archives;
 mod figure;
 mod icons;
 mod maths;
@@ -14,6 +13,7 @@ use std::ffi::CString;
 use std::fs;
 use std::ops::Index;
 use std::rc::Rc;
+use vetor_core::archives;
 use window::*;
 
 fn u864(s: &str) -> [u8; 64] {
@@ -60,9 +60,14 @@ fn main() {
     handle.gui_load_style(Some(cstr!(selected_style).as_c_str()));
 
     handle.set_target_fps(60);
-    while !handle.window_should_close() {
+    while !window.should_quit {
         // ==== Update ====
 
+        // Unsaved tabs are prompted before closing
+        if handle.window_should_close() {
+            window.quit();
+        }
+
         /// Change current style
         if handle.is_key_pressed(KeyboardKey::KEY_F9) && styles.len() > 0 {
             let index = styles.iter().position(|p| p.ends_with(selected_style));
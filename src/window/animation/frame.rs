@@ -1,45 +1,91 @@
 use raylib::{ffi::LoadRenderTexture, prelude::*, RaylibHandle, RaylibThread};
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-use crate::figure::Figure;
+use crate::{
+    figure::{DrawFigure, Figure},
+    maths::{Easing, Vec2},
+};
+use vetor_core::animation::camera::Shot;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FigureAnimation {
     pub global_index: usize,
     pub local_index: usize,
-    pub moved_edges: HashMap<usize, (Vector2, Vector2)>,
+    pub moved_edges: HashMap<usize, (Vec2, Vec2)>,
     pub figure: Rc<RefCell<Figure>>,
 }
 
+/// Pose data of a frame, without its render textures
+#[derive(Debug, Clone)]
+pub struct FrameState {
+    pub figure_animation: Vec<FigureAnimation>,
+    pub keyframe: bool,
+    pub easing: Easing,
+    pub camera: Option<Shot>,
+    pub hold: u32,
+}
+
+/// Pose data of a frame and its cached miniature.
+/// Frames are drawn on demand into the animation canvas.
 #[derive(Debug)]
 pub struct Frame {
-    pub texture: Rc<RefCell<RenderTexture2D>>,
     pub miniature: Option<Texture2D>,
     pub figure_animation: Vec<FigureAnimation>,
     pub is_selected: bool,
+    /// Keyframes are posed by hand, frames in between two keyframes are tweened.
+    pub keyframe: bool,
+    /// Timing curve from this keyframe to the next one.
+    pub easing: Easing,
+    /// Camera key, frames without key interpolate the camera.
+    pub camera: Option<Shot>,
+    /// Ticks this frame stays on screen, while playing and on exported videos.
+    pub hold: u32,
 }
 
 impl Frame {
-    pub fn new(handle: &mut RaylibHandle, thread: &RaylibThread, width: u32, height: u32) -> Frame {
+    pub fn new() -> Frame {
         Frame {
             figure_animation: vec![],
             is_selected: false,
+            keyframe: false,
+            easing: Easing::LINEAR,
+            camera: None,
+            hold: 1,
             miniature: None,
-            texture: Rc::new(RefCell::new(
-                handle
-                    .load_render_texture(thread, width, height)
-                    .ok()
-                    .unwrap(),
-            )),
         }
     }
 
-    pub fn clone(&mut self, texture: RenderTexture2D) -> Frame {
+    /// Create a frame from pose data, without miniature
+    pub fn from_state(state: FrameState) -> Frame {
+        let mut frame = Frame::new();
+        frame.figure_animation = state.figure_animation;
+        frame.keyframe = state.keyframe;
+        frame.easing = state.easing;
+        frame.camera = state.camera;
+        frame.hold = state.hold;
+        frame
+    }
+
+    pub fn state(&self) -> FrameState {
+        FrameState {
+            figure_animation: self.figure_animation.clone(),
+            keyframe: self.keyframe,
+            easing: self.easing,
+            camera: self.camera,
+            hold: self.hold,
+        }
+    }
+
+    /// Copy pose data, the miniature must be rendered again
+    pub fn clone(&self) -> Frame {
         Frame {
             is_selected: self.is_selected,
+            keyframe: self.keyframe,
+            easing: self.easing,
+            camera: self.camera,
+            hold: self.hold,
             figure_animation: self.figure_animation.clone(),
             miniature: None,
-            texture: Rc::new(RefCell::new(texture)),
         }
     }
 
@@ -59,10 +105,24 @@ impl Frame {
         }
     }
 
-    // Render screen texture
-    pub fn render_screen(&mut self, draw_handle: &mut RaylibDrawHandle, thread: &RaylibThread) {
-        let mut texture = self.texture.borrow_mut();
-        let mut draw_texture = draw_handle.begin_texture_mode(thread, &mut texture);
+    // Render frame on the canvas texture, with ghost figures (onion skin) below frame figures
+    pub fn render_screen(
+        &mut self,
+        draw_handle: &mut RaylibDrawHandle,
+        thread: &RaylibThread,
+        canvas: &mut RenderTexture2D,
+        ghosts: &[(FigureAnimation, Color)],
+    ) {
+        let mut draw_texture = draw_handle.begin_texture_mode(thread, canvas);
+
+        draw_texture.clear_background(Color::RAYWHITE);
+
+        for (ghost, tint) in ghosts {
+            ghost
+                .figure
+                .borrow()
+                .draw_ghost(&mut draw_texture, ghost.moved_edges.clone(), *tint);
+        }
 
         // Draw figures on texture
         for animation in &mut self.figure_animation {
@@ -70,21 +130,50 @@ impl Frame {
         }
     }
 
-    /// Scan and resize screen texture to miniature texture
+    /// Scan and resize the canvas, with this frame rendered on it, to miniature texture
     pub fn render_miniature(
         &mut self,
-        mut handle: &mut RaylibHandle,
+        draw_handle: &mut RaylibDrawHandle,
         thread: &RaylibThread,
+        canvas: &RenderTexture2D,
         width: i32,
         height: i32,
         crop: Rectangle,
     ) {
-        let mut image = self.texture.borrow().texture().load_image().unwrap();
+        let mut target = draw_handle
+            .load_render_texture(thread, width as u32, height as u32)
+            .ok()
+            .unwrap();
+
+        // Scale the crop down on the GPU, only the miniature is read back
+        {
+            let mut draw_texture = draw_handle.begin_texture_mode(thread, &mut target);
+            draw_texture.clear_background(Color::RAYWHITE);
+            // Render textures are stored upside down
+            draw_texture.draw_texture_pro(
+                canvas.texture(),
+                rrect(
+                    crop.x,
+                    canvas.texture.height as f32 - crop.y - crop.height,
+                    crop.width,
+                    -crop.height,
+                ),
+                rrect(0, 0, width, height),
+                Vector2::zero(),
+                0.0,
+                Color::WHITE,
+            );
+        }
+
+        let mut image = target.texture().load_image().unwrap();
         image.flip_vertical();
-        image.crop(crop);
-        image.resize(width, height);
 
-        self.miniature = Some(handle.load_texture_from_image(thread, &image).ok().unwrap());
+        self.miniature = Some(
+            draw_handle
+                .load_texture_from_image(thread, &image)
+                .ok()
+                .unwrap(),
+        );
     }
 
     pub fn save_state(&mut self) {
@@ -93,6 +182,15 @@ impl Frame {
         }
     }
 
+    /// Load frame states into its figures
+    pub fn load_state(&mut self) {
+        for anim in &mut self.figure_animation {
+            anim.figure
+                .borrow_mut()
+                .load_state(anim.moved_edges.clone());
+        }
+    }
+
     pub fn chage_figure_draw(&mut self, draw_points: bool) {
         for state in &mut self.figure_animation {
             state.figure.borrow_mut().draw_option.point = draw_points;
@@ -1,19 +1,23 @@
 pub mod frame;
 
 use self::frame::*;
-use super::{util::button::Button, BACKGROUND};
+use super::{
+    history::History,
+    util::{button::Button, canvas::CanvasView, dialog},
+    BACKGROUND,
+};
 use crate::{
-    archives::{self, FileEncoding},
+    archives::{
+        self,
+        error::{Result, VetorError},
+        header::{FileKind, Header},
+        FileEncoding,
+    },
     cstr,
-    figure::Figure,
+    figure::{DrawFigure, Figure},
     icons::VetorIcons,
     maths::*,
 };
-use flate2::{
-    read::{GzDecoder, ZlibDecoder},
-    write::ZlibEncoder,
-    Compression,
-};
 use native_dialog::FileDialog;
 use raylib::{
     ffi::{CheckCollisionPointRec, GetMonitorHeight, GetMonitorWidth, ImageBlurGaussian, WaitTime},
@@ -28,15 +32,82 @@ use std::{
     fmt::Display,
     fs::{self, File},
     io::{Read, Write},
+    ops::Range,
     path::Path,
     process::{Command, Stdio},
     rc::Rc,
 };
+use vetor_core::{
+    animation::{
+        camera::{self, Camera, CameraPreset, Shot},
+        AnimationData, FrameData,
+    },
+    export::{gif::GifEncoder, png, sheet::SpriteSheet, svg},
+    input::Pointer,
+};
 
 #[derive(Debug, Clone, Copy)]
-enum ExportFormat {
+pub enum ExportFormat {
     MP4 = 0,
     GIF = 1,
+    /// Numbered PNG files, one per frame
+    PNG = 2,
+    /// Single PNG with every frame and a JSON atlas
+    SPRITESHEET = 3,
+    /// Selected frame as vector image
+    SVG = 4,
+    /// Whole animation as SMIL animated vector image
+    SVG_ANIMATED = 5,
+}
+
+/// Corner handles of the video camera
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CameraHandle {
+    MOVE,
+    RESIZE,
+    /// Center of the selected frame shot, dragging it keys the camera
+    SHOT,
+}
+
+/// Side of the camera corner handles, in canvas pixels
+const CAMERA_HANDLE: f32 = 16.0;
+
+/// Reversible operations of the animation timeline
+#[derive(Debug, Clone)]
+enum AnimationCommand {
+    /// Frames inserted one after the other from index
+    AddFrames {
+        index: usize,
+        frames: Vec<FrameState>,
+    },
+    RemoveFrame {
+        index: usize,
+        frame: FrameState,
+    },
+    /// Range of `count` frames starting at `from` moved to start at `to`
+    MoveFrames {
+        from: usize,
+        count: usize,
+        to: usize,
+    },
+    /// The figure cell is added back on redo, so editors linked to it still find it
+    AddFigure {
+        frame: usize,
+        global_index: usize,
+        figure: Rc<RefCell<Figure>>,
+    },
+    /// Figure edited in a figure editor, with its poses on every frame showing it
+    ReplaceFigure {
+        global_index: usize,
+        before: (Figure, Vec<HashMap<usize, (Vec2, Vec2)>>),
+        after: (Figure, Vec<HashMap<usize, (Vec2, Vec2)>>),
+    },
+    /// Figure states of a frame before and after posing
+    Pose {
+        frame: usize,
+        before: Vec<HashMap<usize, (Vec2, Vec2)>>,
+        after: Vec<HashMap<usize, (Vec2, Vec2)>>,
+    },
 }
 
 struct Caroussel {
@@ -47,18 +118,44 @@ struct Caroussel {
     display_width: i32,
     display_height: i32,
     scrollbar_height: i32,
+    /// Pressed frame and mouse position when dragging frames
+    drag: Option<(usize, Vector2)>,
+}
+
+/// Mouse distance before a pressed frame starts being dragged
+const FRAME_DRAG_DISTANCE: f32 = 10.0;
+
+impl Caroussel {
+    /// Gap in between frames nearest to a screen x, 0 is before the first frame
+    fn gap_at(&self, x: f32, frame_count: usize) -> usize {
+        let position = x - self.x as f32 + self.value;
+        let slot = (self.display_width + self.display_gap) as f32;
+
+        ((position / slot).round().max(0.0) as usize).min(frame_count)
+    }
 }
 
 pub struct Animation {
     figures: Vec<Rc<RefCell<Figure>>>,
+    /// Global index of the last figure pressed on the canvas
+    figure_focus: Option<usize>,
     frames: Vec<Frame>,
     selected_frame: usize,
+    /// Frames moved together, always contains the selected frame
+    frame_selection: Range<usize>,
     // Main
-    previous_mouse_pos: Vector2,
-    main_texture: Rc<RefCell<RenderTexture2D>>,
-    main_scroll: Vector2,
-    main_position: Vector2,
+    /// Shared screen of every frame, drawn again when a frame is selected or exported
+    canvas: RenderTexture2D,
+    main_view: CanvasView,
+    fit_figure: Button,
+    fit_camera: Button,
     video_camera: Rectangle,
+    camera_drag: Option<(CameraHandle, Vec2)>,
+    camera_key: Button,
+    /// Miniatures must be cropped again after the camera changes
+    camera_changed: bool,
+    /// Miniatures must be drawn again after a figure changes on every frame
+    figures_changed: bool,
     // Frame scroll
     frame_caroussel: Caroussel,
     // Sidebar
@@ -66,97 +163,128 @@ pub struct Animation {
     save_frame: Button,
     save_animation: Button,
     add_figure: Button,
+    /// Send the last posed figure to a figure editor tab
+    edit_figure: Button,
+    keyframe: Button,
+    // Tweening
+    should_tween: bool,
+    easing_handle: Option<usize>,
+    // Onion skin
+    onion_previous: usize,
+    onion_next: usize,
+    onion_opacity: f32,
+    // History
+    history: History<AnimationCommand>,
+    undo: Button,
+    redo: Button,
+    // Open Animation
+    open_animation: Button,
+    open_prompt: bool,
+    open_confirmed: bool,
+    /// File the animation was opened from or last saved to
+    path: Option<String>,
+    unsaved: bool,
+    error: Option<String>,
     // Play Animation
     play: Button,
     previous_time: f64,
     framerate: f32,
+    metadata: Header,
     // Export Dialog
     export_format: ExportFormat,
+    /// Times exported GIFs repeat after the first play, `None` repeats forever
+    gif_loops: Option<u16>,
     save_format: FileEncoding,
 }
 
 impl Animation {
     pub fn new(handle: &mut RaylibHandle, thread: &RaylibThread) -> Animation {
         let sidebar = rrect(0, 30, 100, handle.get_screen_height() - 30);
-        let frame_position = rvec2(sidebar.width, 30);
 
-        let mut first_frame = Frame::new(handle, thread, BACKGROUND.0, BACKGROUND.1);
+        let mut first_frame = Frame::new();
         first_frame.is_selected = true;
 
-        let mut figure =
-            archives::import_figure("./src/assets/figures/men.vfr", archives::FileEncoding::RAW);
+        let mut figure = archives::import_figure("./src/assets/figures/men.vfr")
+            .expect("Default figure asset should be valid");
 
-        figure.center_to(rvec2(
-            first_frame.texture.try_borrow().ok().unwrap().width() / 2,
-            first_frame.texture.try_borrow().ok().unwrap().height() / 2,
-        ));
+        figure.center_to(rvec2(BACKGROUND.0 / 2, BACKGROUND.1 / 2).into());
 
-        let video_width = 1080;
-        let video_height = 720;
         let start = rvec2(sidebar.x, sidebar.y).add(rvec2(15, 20));
-        let video_camera = rrect(
-            (first_frame
-                .texture
-                .clone()
-                .try_borrow()
-                .ok()
-                .unwrap()
-                .width()
-                / 2)
-                - video_width / 2,
-            (first_frame
-                .texture
-                .clone()
-                .try_borrow()
-                .ok()
-                .unwrap()
-                .height()
-                / 2)
-                - video_height / 2,
-            video_width,
-            video_height,
-        );
-
-        let main_center = rvec2(
-            (handle.get_screen_width() / 2) + frame_position.x as i32,
-            (handle.get_screen_height() / 2) + frame_position.y as i32,
-        );
-
-        let texture_center = rvec2(
-            -(BACKGROUND.0 as i32 / 2) + (main_center.x) as i32,
-            -(BACKGROUND.1 as i32 / 2) + main_center.y as i32,
+        let video_camera = camera_rect(&Camera::default());
+
+        let caroussel_height = 100;
+        let main_rec = rrect(
+            sidebar.width,
+            sidebar.y,
+            handle.get_screen_width() as f32 - sidebar.width,
+            handle.get_screen_height() as f32 - sidebar.y - caroussel_height as f32,
         );
 
         let mut animation = Animation {
             export_format: ExportFormat::GIF,
+            gif_loops: None,
             save_format: FileEncoding::RAW,
             selected_frame: 0,
+            frame_selection: 0..1,
             frame_caroussel: Caroussel {
                 value: 0.0,
                 x: (sidebar.x + sidebar.width) as i32,
                 width: handle.get_screen_width() - sidebar.width as i32,
                 display_gap: 10,
                 display_width: 150,
-                display_height: 100,
+                display_height: caroussel_height,
                 scrollbar_height: 15,
+                drag: None,
             },
             previous_time: 0.0,
             framerate: 5.0,
+            metadata: Header::new(FileKind::ANIMATION),
             add_figure: Button::new(rvec2(sidebar.x, sidebar.y).add(rvec2(10, 160))),
+            edit_figure: Button::new(rvec2(sidebar.x, sidebar.y).add(rvec2(10, 775))),
             save_frame: Button::new(rvec2(sidebar.x, sidebar.y).add(rvec2(10, 200))),
+            keyframe: Button::new(rvec2(sidebar.x, sidebar.y).add(rvec2(10, 240))),
+            should_tween: false,
+            easing_handle: None,
+            onion_previous: 1,
+            onion_next: 0,
+            onion_opacity: 0.3,
+            history: History::new(100),
+            undo: Button::new(rvec2(sidebar.x, sidebar.y).add(rvec2(10, 535))),
+            redo: Button::new(rvec2(sidebar.x, sidebar.y).add(rvec2(55, 535))),
+            open_animation: Button::new(rvec2(sidebar.x, sidebar.y).add(rvec2(10, 575))),
+            open_prompt: false,
+            open_confirmed: false,
+            path: None,
+            unsaved: false,
+            error: None,
             save_animation: Button::dynamic_new(0, 0, start, sidebar.width - 30.0),
             play: Button::dynamic_new(0, 1, start, sidebar.width - 30.0),
-            main_texture: first_frame.texture.clone(),
-            main_scroll: texture_center,
+            canvas: handle
+                .load_render_texture(thread, BACKGROUND.0, BACKGROUND.1)
+                .ok()
+                .unwrap(),
+            main_view: CanvasView::new(BACKGROUND, main_rec),
+            fit_figure: Button::new(Vector2::zero()),
+            fit_camera: Button::new(Vector2::zero()),
             video_camera,
-            previous_mouse_pos: Vector2::zero(),
+            camera_drag: None,
+            camera_key: Button::new(rvec2(sidebar.x, sidebar.y).add(rvec2(10, 615))),
+            camera_changed: false,
+            figures_changed: false,
             figures: vec![],
+            figure_focus: None,
             frames: vec![first_frame],
             sidebar,
-            main_position: frame_position,
         };
 
         animation.save_frame.text = Some(cstr!("Add Frame"));
+        animation.keyframe.text = Some(cstr!("Keyframe"));
+        animation.open_animation.text = Some(cstr!("Open"));
+        animation.camera_key.text = Some(cstr!("Key Camera"));
+        animation.edit_figure.text = Some(cstr!("Edit Figure"));
+        animation.fit_figure.text = Some(cstr!("Figura"));
+        animation.fit_camera.text = Some(cstr!("Câmera"));
+        animation.main_view.fit(animation.shot_rect());
         animation.save_animation.set_icon(
             &mut handle.begin_drawing(thread),
             VetorIcons::ICON_FILE_EXPORT,
@@ -165,58 +293,165 @@ impl Animation {
             &mut handle.begin_drawing(thread),
             VetorIcons::ICON_PLAYER_PLAY,
         );
+        animation
+            .undo
+            .set_icon(&mut handle.begin_drawing(thread), VetorIcons::ICON_UNDO);
+        animation
+            .redo
+            .set_icon(&mut handle.begin_drawing(thread), VetorIcons::ICON_REDO);
         animation.push_figure(figure.clone());
         animation.push_figure(figure);
         animation.update(handle, thread);
-        animation.frames[0].render_screen(&mut handle.begin_drawing(thread), thread);
-        animation.frames[0].render_miniature(
-            handle,
-            thread,
-            animation.frame_caroussel.display_width,
-            animation.frame_caroussel.display_height,
-            animation.video_camera,
-        );
+        animation.render_frame(0, handle, thread);
         animation
     }
 
     pub fn update(&mut self, mut handle: &mut RaylibHandle, thread: &RaylibThread) {
+        if self.should_tween {
+            self.tween(handle, thread);
+        }
+
         if self.play.activated {
             return self.play(handle, thread);
         }
 
-        if self.save_animation.activated {
+        if self.save_animation.activated || self.open_prompt || self.error.is_some() {
+            return;
+        }
+
+        let open = if self.open_animation.activated {
+            self.open_prompt = self.unsaved;
+            !self.unsaved
+        } else {
+            self.open_confirmed
+        };
+
+        if open {
+            self.open_confirmed = false;
+
+            if let Err(error) = self.open(handle, thread) {
+                self.error = Some(error.to_string());
+            }
+
             return;
         }
 
         if self.add_figure.activated {
-            let path = FileDialog::new()
-                .set_location("./src/assets/figures")
-                .add_filter("Vetor Figures", &["vfr"])
-                .show_open_single_file()
-                .expect("Cannot load file with filesytem");
-
-            if path.is_some() {
-                let mut figure = archives::import_figure(
-                    path.unwrap().to_str().unwrap(),
-                    archives::FileEncoding::RAW,
-                );
-                figure.center_to(rvec2(BACKGROUND.0 / 2, BACKGROUND.1 / 2));
-                self.push_figure(figure);
+            let path = dialog::picked_path(
+                FileDialog::new()
+                    .set_location("./src/assets/figures")
+                    .add_filter("Vetor Figures", &["vfr", "vfg", "vfz"])
+                    .show_open_single_file(),
+            );
+
+            match path {
+                Ok(Some(path)) => match archives::import_figure(&path) {
+                    Ok(figure) => self.insert_figure(figure),
+                    Err(error) => self.error = Some(error.to_string()),
+                },
+                Ok(None) => (),
+                Err(error) => self.error = Some(error.to_string()),
             }
         }
 
+        let control = handle.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || handle.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        let shift = handle.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+            || handle.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+
+        if self.undo.activated || (control && !shift && handle.is_key_pressed(KeyboardKey::KEY_Z)) {
+            self.undo(handle, thread);
+        }
+
+        if self.redo.activated || (control && shift && handle.is_key_pressed(KeyboardKey::KEY_Z)) {
+            self.redo(handle, thread);
+        }
+
         if handle.is_key_pressed(KeyboardKey::KEY_DELETE) {
             self.remove_frame();
         }
 
+        // Reorder selected frames
+        let selection = self.selection();
+        if control && handle.is_key_pressed(KeyboardKey::KEY_LEFT) && selection.start > 0 {
+            self.reorder_frames(selection.start - 1);
+        }
+
+        if control
+            && handle.is_key_pressed(KeyboardKey::KEY_RIGHT)
+            && selection.end < self.frames.len()
+        {
+            self.reorder_frames(selection.start + 1);
+        }
+
+        // New frame after the selected one, or before it with shift
+        if !control && handle.is_key_pressed(KeyboardKey::KEY_I) {
+            let index = if shift {
+                self.selected_frame
+            } else {
+                self.selected_frame + 1
+            };
+            self.new_frame(index, handle, thread);
+        }
+
+        if control && handle.is_key_pressed(KeyboardKey::KEY_D) {
+            self.duplicate_frames(handle, thread);
+        }
+
+        if self.camera_key.activated {
+            let shot = self.shot(self.selected_frame);
+            let frame = &mut self.frames[self.selected_frame];
+            frame.camera = if frame.camera.is_some() {
+                None
+            } else {
+                Some(shot)
+            };
+            self.unsaved = true;
+        }
+
+        if self.keyframe.activated || handle.is_key_pressed(KeyboardKey::KEY_K) {
+            let frame = &mut self.frames[self.selected_frame];
+            frame.keyframe = !frame.keyframe;
+            self.should_tween = true;
+            self.unsaved = true;
+        }
+
+        if self.camera_changed || self.figures_changed {
+            self.camera_changed = false;
+            self.figures_changed = false;
+            self.render_miniatures(handle, thread);
+        }
+
+        if self.fit_camera.activated || (!control && handle.is_key_pressed(KeyboardKey::KEY_C)) {
+            self.main_view.fit(self.shot_rect());
+        }
+
+        if self.fit_figure.activated || (!control && handle.is_key_pressed(KeyboardKey::KEY_F)) {
+            if let Some(rect) = self.figure_rect() {
+                self.main_view.fit(rect);
+            }
+        }
+
+        if self.main_view.reset_zoom.activated
+            || (control && handle.is_key_pressed(KeyboardKey::KEY_ZERO))
+        {
+            self.main_view.reset();
+        }
+
         let frame_count = self.frames.len() as i32;
+        let pointer = self.main_view.pointer(handle);
+
+        if self.drag_camera(handle, &pointer) {
+            return;
+        }
+
         let mut frame = &mut self.frames[self.selected_frame];
 
         // Update figures and animation state
         for index in 0..frame.figure_animation.len() {
             match frame.figure_animation[index].figure.try_borrow_mut() {
                 Ok(mut figure) => {
-                    figure.update(handle, self.main_position.add(self.main_scroll));
+                    figure.update(&pointer, self.main_view.origin());
                 }
                 _ => (),
             }
@@ -236,6 +471,7 @@ impl Animation {
                     .selected
                     .is_some()
                 {
+                    self.figure_focus = Some(frame.figure_animation[index].global_index);
                     frame.disable_except(index);
                     break;
                 }
@@ -246,10 +482,39 @@ impl Animation {
             frame.enable_all();
         }
 
+        // Register poses changed by dragging figures
+        if handle.is_mouse_button_released(MouseButton::MOUSE_BUTTON_LEFT) {
+            let before: Vec<_> = frame
+                .figure_animation
+                .iter()
+                .map(|anim| anim.moved_edges.clone())
+                .collect();
+            frame.save_state();
+            let after: Vec<_> = frame
+                .figure_animation
+                .iter()
+                .map(|anim| anim.moved_edges.clone())
+                .collect();
+
+            if before != after {
+                self.unsaved = true;
+                self.history.push(AnimationCommand::Pose {
+                    frame: self.selected_frame,
+                    before,
+                    after,
+                });
+
+                // Posing a keyframe changes the tweened frames around it
+                self.should_tween = self.should_tween || frame.keyframe;
+            }
+        }
+
         if self.save_frame.activated {
             self.push_frame(handle, thread);
         }
 
+        self.drop_frames(handle);
+
         self.frame_caroussel.width = handle.get_screen_width() - self.sidebar.width as i32;
         if handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
             let max_width = (frame_count * self.frame_caroussel.display_width)
@@ -290,42 +555,109 @@ impl Animation {
                     CheckCollisionPointRec(handle.get_mouse_position().into(), frame_rect.into())
                 } && handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
                 {
-                    self.select_frame(i as usize);
+                    let index = i as usize;
+
+                    // Shift extends the selection, pressing a selected range drags all of it
+                    if shift {
+                        self.extend_selection(index);
+                    } else {
+                        if !self.selection().contains(&index) {
+                            self.select_frame(index);
+                        }
+
+                        self.frame_caroussel.drag = Some((index, handle.get_mouse_position()));
+                    }
                 }
             }
         }
     }
 
     pub fn draw(&mut self, draw_handle: &mut RaylibDrawHandle, thread: &RaylibThread) {
+        // Exported frames and playback must not show the onion skin
+        let ghosts = if self.play.activated || self.save_animation.activated {
+            vec![]
+        } else {
+            self.onion_ghosts()
+        };
         let mut frame = &mut self.frames[self.selected_frame];
 
-        frame.render_screen(draw_handle, thread);
+        frame.render_screen(draw_handle, thread, &mut self.canvas, &ghosts);
 
         let max_scroll_width = (self.frames.len() as i32 * self.frame_caroussel.display_width)
             + (self.frames.len() - 1) as i32 * self.frame_caroussel.display_gap;
 
         // Draw Main Frame
         {
-            let mut main_texture = self.main_texture.try_borrow_mut().ok().unwrap();
+            let shot = self.shot(self.selected_frame);
+            let main_texture = &mut self.canvas;
             // Draw video camera
             {
-                let mut draw = draw_handle.begin_texture_mode(thread, &mut main_texture);
+                let mut draw = draw_handle.begin_texture_mode(thread, main_texture);
 
                 draw.draw_rectangle_lines_ex(self.video_camera, 1.0, Color::BLACK);
-            }
-            let mut texture_rec = rrect(
-                0,
-                0,
-                main_texture.texture.width,
-                main_texture.texture.height,
-            );
 
+                let size = CAMERA_HANDLE;
+                let corner = rvec2(
+                    self.video_camera.x + self.video_camera.width,
+                    self.video_camera.y + self.video_camera.height,
+                );
+                draw.draw_rectangle_rec(
+                    rrect(
+                        self.video_camera.x - size / 2.0,
+                        self.video_camera.y - size / 2.0,
+                        size,
+                        size,
+                    ),
+                    Color::BLACK,
+                );
+                draw.draw_rectangle_rec(
+                    rrect(corner.x - size / 2.0, corner.y - size / 2.0, size, size),
+                    Color::BLACK,
+                );
+                draw.draw_text(
+                    &format!("{}x{}", self.video_camera.width, self.video_camera.height),
+                    self.video_camera.x as i32 + size as i32,
+                    self.video_camera.y as i32 - 24,
+                    20,
+                    Color::BLACK,
+                );
+
+                // Shot of the selected frame, red when keyed on it
+                let canvas_center = Vec2::new(BACKGROUND.0 as f32 / 2.0, BACKGROUND.1 as f32 / 2.0);
+                let shot_color = if self.frames[self.selected_frame].camera.is_some() {
+                    Color::RED
+                } else {
+                    Color::GRAY
+                };
+
+                if self.frames.iter().any(|frame| frame.camera.is_some()) {
+                    let (x, y) = (
+                        self.video_camera.width / 2.0,
+                        self.video_camera.height / 2.0,
+                    );
+                    let corners: Vec<Vector2> = [(-x, -y), (x, -y), (x, y), (-x, y)]
+                        .iter()
+                        .map(|(x, y)| shot.to_canvas(Vec2::new(*x, *y)).add(canvas_center).into())
+                        .collect();
+
+                    for i in 0..corners.len() {
+                        draw.draw_line_ex(
+                            corners[i],
+                            corners[(i + 1) % corners.len()],
+                            2.0,
+                            shot_color,
+                        );
+                    }
+                }
+
+                draw.draw_circle_v(shot.center.add(canvas_center), size / 2.0, shot_color);
+            }
             let main_rec = rrect(
-                self.main_position.x,
-                self.main_position.y,
+                self.sidebar.width,
+                self.sidebar.y,
                 draw_handle.get_screen_width() as f32 - self.sidebar.width,
                 draw_handle.get_screen_height() as f32
-                    - self.main_position.y
+                    - self.sidebar.y
                     - (self.frame_caroussel.display_height as f32
                         + if max_scroll_width < self.frame_caroussel.width {
                             0.0
@@ -334,56 +666,11 @@ impl Animation {
                         }),
             );
 
-            let (scissor_rec, main_scroll) =
-                draw_handle.gui_scroll_panel(main_rec, None, texture_rec, self.main_scroll);
-            self.main_scroll = main_scroll;
-
-            // Draw main screen texture
-            let mut scissor = draw_handle.begin_scissor_mode(
-                scissor_rec.x as i32,
-                scissor_rec.y as i32,
-                scissor_rec.width as i32,
-                scissor_rec.height as i32,
-            );
-
-            let mouse_pos = scissor.get_mouse_position();
-
-            // Drag background scroll
-            if scissor.is_mouse_button_down(MouseButton::MOUSE_BUTTON_MIDDLE)
-                && scissor_rec.check_collision_point_rec(mouse_pos)
-            {
-                let delta = mouse_pos.sub(self.previous_mouse_pos);
-                self.main_scroll.x += delta.x;
-                self.main_scroll.y += delta.y;
-
-                let max_scroll_x = texture_rec.width - scissor_rec.width;
-                let max_scroll_y = texture_rec.height - scissor_rec.height;
-
-                if self.main_scroll.x > 0.0 {
-                    self.main_scroll.x = 0.0;
-                } else if -self.main_scroll.x >= max_scroll_x {
-                    self.main_scroll.x = -max_scroll_x + 1.0;
-                }
-
-                if self.main_scroll.y > 0.0 {
-                    self.main_scroll.y = 0.0;
-                } else if -self.main_scroll.y >= max_scroll_y {
-                    self.main_scroll.y = -max_scroll_y + 1.0;
-                }
-            }
-
-            texture_rec.x -= self.main_scroll.x;
-            texture_rec.y += self.main_scroll.y;
-            // Invert texture rect height to apply correct perspective
-            texture_rec.height = -texture_rec.height;
-            scissor.draw_texture_rec(
-                main_texture.texture(),
-                texture_rec,
-                self.main_position,
-                Color::RAYWHITE.fade(1.0),
+            self.main_view.draw(draw_handle, main_texture, main_rec);
+            self.main_view.draw_zoom_bar(
+                draw_handle,
+                &mut [&mut self.fit_figure, &mut self.fit_camera],
             );
-
-            self.previous_mouse_pos = mouse_pos;
         }
 
         // Draw sidebar
@@ -506,6 +793,112 @@ impl Animation {
                 ),
                 Some(rstr!("Add Figure")),
             );
+
+            let keyframe = self.frames[self.selected_frame].keyframe;
+            self.keyframe.activated = draw_handle.gui_toggle(
+                rrect(
+                    self.keyframe.start.x,
+                    self.keyframe.start.y,
+                    self.sidebar.width - 20.0,
+                    30,
+                ),
+                Some(self.keyframe.text.clone().unwrap().as_c_str()),
+                keyframe,
+            ) != keyframe;
+
+            if keyframe {
+                self.draw_easing_editor(draw_handle);
+            }
+
+            // Onion skin settings
+            draw_handle.gui_label(
+                rrect(15, self.sidebar.y + 410.0, self.sidebar.width - 30.0, 20),
+                Some(cstr!(format!("Prev {}", self.onion_previous)).as_c_str()),
+            );
+            self.onion_previous = draw_handle
+                .gui_slider(
+                    rrect(
+                        self.sidebar.x + 15.0,
+                        self.sidebar.y + 430.0,
+                        self.sidebar.width - 30.0,
+                        15.0,
+                    ),
+                    None,
+                    None,
+                    self.onion_previous as f32,
+                    0.0,
+                    5.0,
+                )
+                .round() as usize;
+
+            draw_handle.gui_label(
+                rrect(15, self.sidebar.y + 450.0, self.sidebar.width - 30.0, 20),
+                Some(cstr!(format!("Next {}", self.onion_next)).as_c_str()),
+            );
+            self.onion_next = draw_handle
+                .gui_slider(
+                    rrect(
+                        self.sidebar.x + 15.0,
+                        self.sidebar.y + 470.0,
+                        self.sidebar.width - 30.0,
+                        15.0,
+                    ),
+                    None,
+                    None,
+                    self.onion_next as f32,
+                    0.0,
+                    5.0,
+                )
+                .round() as usize;
+
+            draw_handle.gui_label(
+                rrect(15, self.sidebar.y + 490.0, self.sidebar.width - 30.0, 20),
+                Some(cstr!(format!("Onion {}%", (self.onion_opacity * 100.0).round())).as_c_str()),
+            );
+            self.onion_opacity = draw_handle.gui_slider(
+                rrect(
+                    self.sidebar.x + 15.0,
+                    self.sidebar.y + 510.0,
+                    self.sidebar.width - 30.0,
+                    15.0,
+                ),
+                None,
+                None,
+                self.onion_opacity,
+                0.05,
+                1.0,
+            );
+
+            for btn in [&mut self.undo, &mut self.redo] {
+                btn.activated = draw_handle.gui_button(
+                    rrect(btn.start.x, btn.start.y, 35, 30),
+                    Some(btn.text.clone().unwrap().as_c_str()),
+                );
+            }
+
+            self.open_animation.activated = draw_handle.gui_button(
+                rrect(
+                    self.open_animation.start.x,
+                    self.open_animation.start.y,
+                    self.sidebar.width - 20.0,
+                    30,
+                ),
+                Some(self.open_animation.text.clone().unwrap().as_c_str()),
+            ) && !self.play.activated;
+
+            self.draw_camera_editor(draw_handle);
+            self.draw_hold_editor(draw_handle);
+
+            self.edit_figure.activated = draw_handle.gui_button(
+                rrect(
+                    self.edit_figure.start.x,
+                    self.edit_figure.start.y,
+                    self.sidebar.width - 20.0,
+                    30,
+                ),
+                Some(self.edit_figure.text.clone().unwrap().as_c_str()),
+            ) && !self.play.activated
+                || self.edit_figure.activated;
         }
 
         // Draw animation frames
@@ -546,6 +939,7 @@ impl Animation {
             );
 
             // Draw frames
+            let selection = self.selection();
             for i in 0..self.frames.len() {
                 let moved_content = i as i32 * self.frame_caroussel.display_width
                     + i as i32 * self.frame_caroussel.display_gap
@@ -579,6 +973,11 @@ impl Animation {
                             GuiControl::DEFAULT,
                             GuiControlProperty::BORDER_COLOR_PRESSED as i32,
                         ) as u32)
+                    } else if selection.contains(&i) {
+                        Color::get_color(scissor.gui_get_style(
+                            GuiControl::DEFAULT,
+                            GuiControlProperty::BORDER_COLOR_FOCUSED as i32,
+                        ) as u32)
                     } else {
                         Color::get_color(scissor.gui_get_style(
                             GuiControl::DEFAULT,
@@ -586,6 +985,54 @@ impl Animation {
                         ) as u32)
                     },
                 );
+
+                // Mark keyframes at thumbnail corner
+                if self.frames[i].keyframe {
+                    scissor.draw_circle(
+                        self.frame_caroussel.x + moved_content + 12,
+                        y + 12,
+                        6.0,
+                        Color::ORANGE,
+                    );
+                }
+
+                // Held frames show their ticks at the other corner
+                if self.frames[i].hold > 1 {
+                    scissor.draw_text(
+                        &format!("x{}", self.frames[i].hold),
+                        self.frame_caroussel.x + moved_content + self.frame_caroussel.display_width
+                            - 30,
+                        y + 6,
+                        14,
+                        Color::DARKGRAY,
+                    );
+                }
+            }
+
+            // Mark where dragged frames will be dropped
+            if let Some((_, start)) = self.frame_caroussel.drag {
+                let mouse = scissor.get_mouse_position();
+
+                if (mouse.x - start.x).abs() >= FRAME_DRAG_DISTANCE {
+                    let gap = self.frame_caroussel.gap_at(mouse.x, self.frames.len()) as i32;
+                    let x = self.frame_caroussel.x
+                        + gap
+                            * (self.frame_caroussel.display_width
+                                + self.frame_caroussel.display_gap)
+                        - self.frame_caroussel.display_gap / 2
+                        - self.frame_caroussel.value as i32;
+
+                    scissor.draw_rectangle(
+                        x - 2,
+                        y,
+                        4,
+                        height - scrollbar_height,
+                        Color::get_color(scissor.gui_get_style(
+                            GuiControl::DEFAULT,
+                            GuiControlProperty::BORDER_COLOR_PRESSED as i32,
+                        ) as u32),
+                    );
+                }
             }
 
             // Avoid draw scrollbar when frames is less then width
@@ -640,6 +1087,218 @@ impl Animation {
         if self.save_animation.activated {
             self.draw_export_dialog(draw_handle, thread);
         }
+
+        if self.open_prompt {
+            self.draw_open_prompt(draw_handle);
+        }
+
+        dialog::draw_error(draw_handle, &mut self.error);
+    }
+
+    /// Ask to discard unsaved changes before opening another animation
+    fn draw_open_prompt(&mut self, draw_handle: &mut RaylibDrawHandle) {
+        let w = draw_handle.get_screen_width();
+        let h = draw_handle.get_screen_height();
+
+        let result = draw_handle.gui_message_box(
+            rrect((w / 2) - 150, (h / 2) - 60, 300, 120),
+            Some(rstr!("Unsaved changes")),
+            Some(rstr!("Discard changes and open animation?")),
+            Some(rstr!("Discard;Cancel")),
+        );
+
+        // -1 while the box is open, 0 when closed on the window button
+        if result >= 0 {
+            self.open_prompt = false;
+            self.open_confirmed = result == 1;
+        }
+    }
+
+    /// Draw camera key toggle with zoom and rotation of the selected frame shot.
+    /// Changing the shot keys the camera on the selected frame.
+    fn draw_camera_editor(&mut self, draw_handle: &mut RaylibDrawHandle) {
+        let keyed = self.frames[self.selected_frame].camera.is_some();
+        self.camera_key.activated = draw_handle.gui_toggle(
+            rrect(
+                self.camera_key.start.x,
+                self.camera_key.start.y,
+                self.sidebar.width - 20.0,
+                30,
+            ),
+            Some(self.camera_key.text.clone().unwrap().as_c_str()),
+            keyed,
+        ) != keyed;
+
+        let mut shot = self.shot(self.selected_frame);
+        let zoom_bar = rrect(
+            self.sidebar.x + 15.0,
+            self.sidebar.y + 670.0,
+            self.sidebar.width - 30.0,
+            15.0,
+        );
+        let rotation_bar = rrect(
+            self.sidebar.x + 15.0,
+            self.sidebar.y + 710.0,
+            self.sidebar.width - 30.0,
+            15.0,
+        );
+
+        draw_handle.gui_label(
+            rrect(15, self.sidebar.y + 650.0, self.sidebar.width - 30.0, 20),
+            Some(cstr!(format!("Zoom {:.2}x", shot.zoom)).as_c_str()),
+        );
+        let zoom = draw_handle.gui_slider(zoom_bar, None, None, shot.zoom, 0.25, 4.0);
+
+        draw_handle.gui_label(
+            rrect(15, self.sidebar.y + 690.0, self.sidebar.width - 30.0, 20),
+            Some(cstr!(format!("Rotação {}°", shot.rotation)).as_c_str()),
+        );
+        let rotation =
+            draw_handle.gui_slider(rotation_bar, None, None, shot.rotation, -180.0, 180.0);
+
+        // Sliders clamp the shot on every call, only key values moved by the user
+        let mouse_pos = draw_handle.get_mouse_position();
+        let pressed = draw_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT);
+
+        if pressed && zoom_bar.check_collision_point_rec(mouse_pos) && zoom != shot.zoom {
+            shot.zoom = zoom;
+            self.set_shot(shot);
+        }
+
+        if pressed
+            && rotation_bar.check_collision_point_rec(mouse_pos)
+            && rotation.round() != shot.rotation
+        {
+            shot.rotation = rotation.round();
+            self.set_shot(shot);
+        }
+    }
+
+    /// Draw how many ticks the selected frame stays on screen
+    fn draw_hold_editor(&mut self, draw_handle: &mut RaylibDrawHandle) {
+        let hold = self.frames[self.selected_frame].hold;
+        let hold_bar = rrect(
+            self.sidebar.x + 15.0,
+            self.sidebar.y + 750.0,
+            self.sidebar.width - 30.0,
+            15.0,
+        );
+
+        draw_handle.gui_label(
+            rrect(15, self.sidebar.y + 730.0, self.sidebar.width - 30.0, 20),
+            Some(cstr!(format!("Hold {}", hold)).as_c_str()),
+        );
+        let new_hold = draw_handle
+            .gui_slider(hold_bar, None, None, hold as f32, 1.0, 12.0)
+            .round() as u32;
+
+        // Files may hold frames longer than the slider range, keep them until it is moved
+        let mouse_pos = draw_handle.get_mouse_position();
+        let pressed = draw_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT);
+
+        if pressed && hold_bar.check_collision_point_rec(mouse_pos) && new_hold != hold {
+            self.frames[self.selected_frame].hold = new_hold;
+            self.unsaved = true;
+        }
+    }
+
+    /// Draw easing selector of selected keyframe and the handles editor of bezier curves
+    fn draw_easing_editor(&mut self, draw_handle: &mut RaylibDrawHandle) {
+        let easing = self.frames[self.selected_frame].easing;
+        let easing_index: i32 = easing.into();
+
+        let active = draw_handle.gui_combo_box(
+            rrect(
+                self.sidebar.x + 10.0,
+                self.sidebar.y + 280.0,
+                self.sidebar.width - 20.0,
+                30,
+            ),
+            Some(rstr!("linear;in;out;in-out;bezier")),
+            easing_index,
+        );
+
+        let mut next = if active == easing_index {
+            easing
+        } else {
+            Easing::from(active)
+        };
+
+        if let Easing::BEZIER(x1, y1, x2, y2) = next {
+            let area = rrect(
+                self.sidebar.x + 10.0,
+                self.sidebar.y + 320.0,
+                self.sidebar.width - 20.0,
+                self.sidebar.width - 20.0,
+            );
+            let to_screen =
+                |x: f32, y: f32| rvec2(area.x + x * area.width, area.y + (1.0 - y) * area.height);
+            let mut handles = [rvec2(x1, y1), rvec2(x2, y2)];
+            let mouse_pos = draw_handle.get_mouse_position();
+
+            if draw_handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+                self.easing_handle = handles.iter().position(|h| {
+                    check_collision_point_circle(mouse_pos, to_screen(h.x, h.y), 6.0)
+                });
+            }
+
+            // Drag handle inside curve area and generate frames after release
+            if let Some(index) = self.easing_handle {
+                if draw_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+                    handles[index] = rvec2(
+                        ((mouse_pos.x - area.x) / area.width).clamp(0.0, 1.0),
+                        (1.0 - (mouse_pos.y - area.y) / area.height).clamp(0.0, 1.0),
+                    );
+                } else {
+                    self.easing_handle = None;
+                    self.should_tween = true;
+                }
+            }
+
+            next = Easing::BEZIER(handles[0].x, handles[0].y, handles[1].x, handles[1].y);
+
+            let line_color = Color::get_color(draw_handle.gui_get_style(
+                GuiControl::DEFAULT,
+                GuiControlProperty::BORDER_COLOR_NORMAL as i32,
+            ) as u32);
+            let handle_color = Color::get_color(draw_handle.gui_get_style(
+                GuiControl::DEFAULT,
+                GuiControlProperty::BORDER_COLOR_PRESSED as i32,
+            ) as u32);
+
+            draw_handle.draw_rectangle_lines_ex(area, 1.0, line_color);
+            draw_handle.draw_line_v(
+                to_screen(0.0, 0.0),
+                to_screen(handles[0].x, handles[0].y),
+                handle_color,
+            );
+            draw_handle.draw_line_v(
+                to_screen(1.0, 1.0),
+                to_screen(handles[1].x, handles[1].y),
+                handle_color,
+            );
+
+            let mut previous = to_screen(0.0, 0.0);
+            for step in 1..=24 {
+                let t = step as f32 / 24.0;
+                let point = to_screen(
+                    cubic_bezier(handles[0].x, handles[1].x, t),
+                    cubic_bezier(handles[0].y, handles[1].y, t),
+                );
+                draw_handle.draw_line_ex(previous, point, 2.0, line_color);
+                previous = point;
+            }
+
+            for h in handles {
+                draw_handle.draw_circle_v(to_screen(h.x, h.y), 5.0, handle_color);
+            }
+        }
+
+        if next != easing {
+            self.frames[self.selected_frame].easing = next;
+            self.unsaved = true;
+            self.should_tween = self.should_tween || self.easing_handle.is_none();
+        }
     }
 
     fn draw_export_dialog(&mut self, draw_handle: &mut RaylibDrawHandle, thread: &RaylibThread) {
@@ -659,36 +1318,78 @@ impl Animation {
             .fade(0.3),
         );
 
-        let dialog_rect = rrect((w / 2) - 150, (h / 2) - 100, 300, 136);
+        let dialog_rect = rrect((w / 2) - 150, (h / 2) - 100, 300, 206);
 
         self.save_animation.activated =
             !draw_handle.gui_window_box(dialog_rect, Some(rstr!("Exportar como:")));
 
         let export = draw_handle.gui_combo_box(
             rrect(dialog_rect.x + 25.0, dialog_rect.y + 40.0, 120, 30),
-            Some(rstr!("mp4;gif")),
+            Some(rstr!("mp4;gif;png;sprite sheet;svg;svg animado")),
             self.export_format as i32,
         );
 
         self.export_format = match export {
             0 => ExportFormat::MP4,
             1 => ExportFormat::GIF,
+            2 => ExportFormat::PNG,
+            3 => ExportFormat::SPRITESHEET,
+            4 => ExportFormat::SVG,
+            5 => ExportFormat::SVG_ANIMATED,
             _ => ExportFormat::MP4,
         };
 
-        if draw_handle.gui_button(
-            rrect(dialog_rect.x + 25.0, dialog_rect.y + 80.0, 120, 30),
-            Some(rstr!("Exportar")),
+        if let ExportFormat::GIF = self.export_format {
+            let forever = draw_handle.gui_check_box(
+                rrect(dialog_rect.x + 25.0, dialog_rect.y + 125.0, 20, 20),
+                Some(rstr!("Loop")),
+                self.gif_loops.is_none(),
+            );
+
+            self.gif_loops = if forever {
+                None
+            } else {
+                let mut loops = self.gif_loops.unwrap_or(0) as i32;
+                draw_handle.gui_spinner(
+                    rrect(dialog_rect.x + 154.0, dialog_rect.y + 120.0, 120, 30),
+                    None,
+                    &mut loops,
+                    0,
+                    100,
+                    false,
+                );
+
+                Some(loops as u16)
+            };
+        }
+
+        // Camera presets, manual camera sizes are shown as custom
+        let mut camera = self.camera();
+        let presets = CameraPreset::ALL.len() as i32;
+        let preset = CameraPreset::find(camera.size)
+            .and_then(|preset| CameraPreset::ALL.iter().position(|p| *p == preset))
+            .map(|index| index as i32)
+            .unwrap_or(presets);
+
+        let selected = draw_handle.gui_combo_box(
+            rrect(dialog_rect.x + 25.0, dialog_rect.y + 160.0, 249, 30),
+            Some(rstr!("1080x720;720p;1080p;quadrado;vertical;personalizado")),
+            preset,
+        );
+
+        if selected != preset && selected < presets {
+            camera.resize(CameraPreset::ALL[selected as usize].size());
+            camera.clamp_to(BACKGROUND);
+            self.set_camera(camera);
+        }
+
+        if draw_handle.gui_button(
+            rrect(dialog_rect.x + 25.0, dialog_rect.y + 80.0, 120, 30),
+            Some(rstr!("Exportar")),
         ) {
-            match self.export_format {
-                ExportFormat::GIF => {
-                    self.export("unnamed", "gif");
-                }
-                ExportFormat::MP4 => {
-                    self.export("unnamed", "mp4");
-                }
+            if let Err(error) = self.export(draw_handle, thread, "unnamed", self.export_format) {
+                self.error = Some(error.to_string());
             }
-            println!();
 
             self.save_animation.activated = false;
         }
@@ -715,95 +1416,735 @@ impl Animation {
             rrect(dialog_rect.x + 154.0, dialog_rect.y + 80.0, 120, 30),
             Some(rstr!("Salvar")),
         ) {
-            self.save("unnamed");
+            if let Err(error) = self.save("unnamed") {
+                self.error = Some(error.to_string());
+            }
+
             self.save_animation.activated = false;
         }
     }
 
+    /// Add a figure, centered on the canvas, to the selected frame as an undoable action
+    pub fn insert_figure(&mut self, mut figure: Figure) {
+        figure.center_to(rvec2(BACKGROUND.0 / 2, BACKGROUND.1 / 2).into());
+        let figure = Rc::new(RefCell::new(figure));
+        self.add_figure(self.figures.len(), figure.clone());
+        self.record(AnimationCommand::AddFigure {
+            frame: self.selected_frame,
+            global_index: self.figures.len() - 1,
+            figure,
+        });
+    }
+
+    /// Figure the user asked to edit, posed as in the selected frame, with the cell it is
+    /// kept in, which identifies it while indexes shift. Defaults to the last added figure
+    /// when none was pressed.
+    pub fn take_sent_figure(&mut self) -> Option<(Rc<RefCell<Figure>>, Figure)> {
+        if !self.edit_figure.activated {
+            return None;
+        }
+
+        self.edit_figure.activated = false;
+        let index = self
+            .figure_focus
+            .filter(|index| *index < self.figures.len())
+            .or(self.figures.len().checked_sub(1))?;
+
+        Some((
+            self.figures[index].clone(),
+            self.figures[index].try_borrow().ok().unwrap().clone(),
+        ))
+    }
+
+    /// Replace a figure by its edited copy as an undoable action, the poses
+    /// of every frame are carried over to the matching edited edges.
+    /// Figures removed since they were sent are added again instead.
+    pub fn replace_figure(&mut self, source: &Rc<RefCell<Figure>>, figure: Figure) {
+        let global_index = match self.figures.iter().position(|f| Rc::ptr_eq(f, source)) {
+            Some(index) => index,
+            None => return self.insert_figure(figure),
+        };
+
+        self.frames[self.selected_frame].save_state();
+
+        let before = self.figures[global_index]
+            .try_borrow()
+            .ok()
+            .unwrap()
+            .clone();
+        let states = self.figure_states(global_index);
+        let matches = before.match_edges(&figure);
+        let remapped: Vec<_> = states
+            .iter()
+            .map(|state| before.remap_state(&figure, &matches, state))
+            .collect();
+
+        self.set_figure(global_index, figure.clone(), remapped.clone());
+        self.record(AnimationCommand::ReplaceFigure {
+            global_index,
+            before: (before, states),
+            after: (figure, remapped),
+        });
+    }
+
+    /// Poses of a figure on every frame showing it, in frame order
+    fn figure_states(&self, global_index: usize) -> Vec<HashMap<usize, (Vec2, Vec2)>> {
+        self.frames
+            .iter()
+            .flat_map(|frame| &frame.figure_animation)
+            .filter(|anim| anim.global_index == global_index)
+            .map(|anim| anim.moved_edges.clone())
+            .collect()
+    }
+
+    /// Set a figure tree and its poses, as given by `figure_states`
+    fn set_figure(
+        &mut self,
+        global_index: usize,
+        figure: Figure,
+        states: Vec<HashMap<usize, (Vec2, Vec2)>>,
+    ) {
+        *self.figures[global_index].try_borrow_mut().ok().unwrap() = figure;
+
+        let anims = self
+            .frames
+            .iter_mut()
+            .flat_map(|frame| &mut frame.figure_animation)
+            .filter(|anim| anim.global_index == global_index);
+
+        for (anim, state) in anims.zip(states) {
+            anim.moved_edges = state;
+        }
+
+        self.frames[self.selected_frame].load_state();
+        self.figures_changed = true;
+        self.should_tween = true;
+    }
+
     pub fn push_figure(&mut self, figure: Figure) {
-        self.figures.push(Rc::new(RefCell::new(figure.clone())));
+        self.add_figure(self.figures.len(), Rc::new(RefCell::new(figure)));
+    }
 
-        let mut frame = &mut self.frames[self.selected_frame];
+    /// Insert a figure at a global index and show it on the selected frame
+    fn add_figure(&mut self, index: usize, figure: Rc<RefCell<Figure>>) {
+        let moved_edges = figure.try_borrow().ok().unwrap().scan();
+        self.figures.insert(index, figure);
+        self.figure_focus = self
+            .figure_focus
+            .map(|focus| if focus >= index { focus + 1 } else { focus });
+
+        for frame in &mut self.frames {
+            for anim in &mut frame.figure_animation {
+                if anim.global_index >= index {
+                    anim.global_index += 1;
+                }
+            }
+        }
+
+        let frame = &mut self.frames[self.selected_frame];
         frame.figure_animation.push(FigureAnimation {
-            global_index: self.figures.len() - 1,
+            global_index: index,
             local_index: frame.figure_animation.len(),
-            moved_edges: figure.scan(),
-            figure: self.figures.last().unwrap().clone(),
+            moved_edges,
+            figure: self.figures[index].clone(),
         });
     }
 
+    /// Remove a figure from the animation and from every frame showing it
+    fn remove_figure(&mut self, index: usize) {
+        self.figures.remove(index);
+        self.figure_focus = match self.figure_focus {
+            Some(focus) if focus > index => Some(focus - 1),
+            Some(focus) if focus < index => Some(focus),
+            _ => None,
+        };
+
+        for frame in &mut self.frames {
+            frame
+                .figure_animation
+                .retain(|anim| anim.global_index != index);
+
+            for (local, anim) in frame.figure_animation.iter_mut().enumerate() {
+                anim.local_index = local;
+
+                if anim.global_index > index {
+                    anim.global_index -= 1;
+                }
+            }
+        }
+    }
+
+    /// Selected frames, ranges left behind by timeline changes fall back to the selected frame
+    fn selection(&self) -> Range<usize> {
+        if self.frame_selection.contains(&self.selected_frame)
+            && self.frame_selection.end <= self.frames.len()
+        {
+            self.frame_selection.clone()
+        } else {
+            self.selected_frame..self.selected_frame + 1
+        }
+    }
+
+    /// Select every frame from the other end of the selection to index
+    fn extend_selection(&mut self, index: usize) {
+        let selection = self.selection();
+        let anchor = if self.selected_frame == selection.end - 1 {
+            selection.start
+        } else {
+            selection.end - 1
+        };
+
+        self.select_frame(index);
+        self.frame_selection = anchor.min(index)..anchor.max(index) + 1;
+    }
+
     fn select_frame(&mut self, index: usize) {
         let mut frame = &mut self.frames[self.selected_frame];
         frame.is_selected = false;
+        // Keep poses edited on the frame we are leaving
+        frame.save_state();
         frame = &mut self.frames[index];
         frame.is_selected = true;
-        self.main_texture = frame.texture.clone();
         self.selected_frame = index;
+        self.frame_selection = index..index + 1;
+        frame.load_state();
+    }
 
-        for fig in &mut frame.figure_animation {
-            fig.figure
-                .try_borrow_mut()
-                .ok()
-                .unwrap()
-                .load_state(fig.moved_edges.clone());
+    /// Collect figure states of previous (red) and next (blue) frames,
+    /// fading out as they get far from the selected frame.
+    fn onion_ghosts(&self) -> Vec<(FigureAnimation, Color)> {
+        let mut ghosts = vec![];
+        let fade = |distance: usize, count: usize| {
+            self.onion_opacity * (count - distance + 1) as f32 / count as f32
+        };
+
+        // Farthest frames first, so the nearest ghosts are drawn on top
+        for distance in (1..=self.onion_previous).rev() {
+            if let Some(index) = self.selected_frame.checked_sub(distance) {
+                let tint = Color::RED.fade(fade(distance, self.onion_previous));
+
+                for anim in &self.frames[index].figure_animation {
+                    ghosts.push((anim.clone(), tint));
+                }
+            }
         }
-    }
 
-    pub fn push_frame(&mut self, handle: &mut RaylibHandle, thread: &RaylibThread) {
-        let mut frame = &mut self.frames[self.selected_frame];
+        for distance in (1..=self.onion_next).rev() {
+            let index = self.selected_frame + distance;
 
-        let texture = handle
-            .load_render_texture(
-                thread,
-                frame.texture.try_borrow().ok().unwrap().width() as u32,
-                frame.texture.try_borrow().ok().unwrap().height() as u32,
-            )
-            .ok()
-            .unwrap();
+            if index < self.frames.len() {
+                let tint = Color::BLUE.fade(fade(distance, self.onion_next));
 
-        frame.chage_figure_draw(false);
+                for anim in &self.frames[index].figure_animation {
+                    ghosts.push((anim.clone(), tint));
+                }
+            }
+        }
 
-        let mut new_frame = frame.clone(texture);
-        new_frame.render_screen(&mut handle.begin_drawing(thread), thread);
-        new_frame.render_miniature(
-            handle,
-            thread,
-            self.frame_caroussel.display_width,
-            self.frame_caroussel.display_height,
-            self.video_camera,
-        );
-        new_frame.save_state();
-        frame.is_selected = false;
+        ghosts
+    }
+
+    /// Render frame screen and miniature with its own figure states
+    fn render_frame(&mut self, index: usize, handle: &mut RaylibHandle, thread: &RaylibThread) {
+        let frame = &mut self.frames[index];
+        let mut draw_handle = handle.begin_drawing(thread);
+
+        frame.load_state();
+        frame.chage_figure_draw(false);
+        frame.render_screen(&mut draw_handle, thread, &mut self.canvas, &[]);
         frame.render_miniature(
-            handle,
+            &mut draw_handle,
             thread,
+            &self.canvas,
             self.frame_caroussel.display_width,
             self.frame_caroussel.display_height,
             self.video_camera,
         );
+        frame.chage_figure_draw(true);
+    }
+
+    fn render_miniatures(&mut self, handle: &mut RaylibHandle, thread: &RaylibThread) {
+        self.frames[self.selected_frame].save_state();
+
+        for index in 0..self.frames.len() {
+            self.render_frame(index, handle, thread);
+        }
+
+        self.frames[self.selected_frame].load_state();
+    }
+
+    /// Camera relative to the canvas center, as stored in files
+    fn camera(&self) -> Camera {
+        let center = Vec2::new(BACKGROUND.0 as f32 / 2.0, BACKGROUND.1 as f32 / 2.0);
+
+        Camera {
+            position: Vec2::new(self.video_camera.x, self.video_camera.y).sub(center),
+            size: (
+                self.video_camera.width as u32,
+                self.video_camera.height as u32,
+            ),
+        }
+    }
+
+    fn set_camera(&mut self, camera: Camera) {
+        if self.camera() != camera {
+            self.video_camera = camera_rect(&camera);
+            self.camera_changed = true;
+            self.unsaved = true;
+        }
+    }
+
+    /// Camera of a frame, interpolated in between camera keys
+    fn shot(&self, index: usize) -> Shot {
+        camera::shot_at(self.camera().shot(), &self.camera_keys(), index)
+    }
+
+    /// Camera of every frame, keys are collected once
+    fn shots(&self) -> Vec<Shot> {
+        let keys = self.camera_keys();
+
+        (0..self.frames.len())
+            .map(|index| camera::shot_at(self.camera().shot(), &keys, index))
+            .collect()
+    }
+
+    /// Frames with a camera key, with the easing towards the next key
+    fn camera_keys(&self) -> Vec<(usize, Shot, Easing)> {
+        self.frames
+            .iter()
+            .enumerate()
+            .filter_map(|(index, frame)| {
+                let easing = if frame.keyframe {
+                    frame.easing
+                } else {
+                    Easing::LINEAR
+                };
+
+                frame.camera.map(|shot| (index, shot, easing))
+            })
+            .collect()
+    }
+
+    /// Canvas area seen by the camera on the selected frame, ignoring its roll
+    fn shot_rect(&self) -> (f32, f32, f32, f32) {
+        let canvas_center = Vec2::new(BACKGROUND.0 as f32 / 2.0, BACKGROUND.1 as f32 / 2.0);
+        let shot = self.shot(self.selected_frame);
+        let center = shot.center.add(canvas_center);
+        let (width, height) = (
+            self.video_camera.width / shot.zoom,
+            self.video_camera.height / shot.zoom,
+        );
+
+        (
+            center.x - width / 2.0,
+            center.y - height / 2.0,
+            width,
+            height,
+        )
+    }
+
+    /// Canvas area of the focused figure, or of every figure on the selected frame
+    fn figure_rect(&self) -> Option<(f32, f32, f32, f32)> {
+        let figure_animation = &self.frames[self.selected_frame].figure_animation;
+        let focused: Vec<_> = figure_animation
+            .iter()
+            .filter(|anim| Some(anim.global_index) == self.figure_focus)
+            .collect();
+        let figures = if focused.is_empty() {
+            figure_animation.iter().collect()
+        } else {
+            focused
+        };
+
+        figures
+            .iter()
+            .map(|anim| anim.figure.try_borrow().ok().unwrap().rect())
+            .reduce(|a, b| {
+                let (x, y) = (a.0.min(b.0), a.1.min(b.1));
+                let right = (a.0 + a.2).max(b.0 + b.2);
+                let bottom = (a.1 + a.3).max(b.1 + b.3);
+                (x, y, right - x, bottom - y)
+            })
+    }
+
+    /// Key the camera on the selected frame
+    fn set_shot(&mut self, shot: Shot) {
+        let frame = &mut self.frames[self.selected_frame];
+
+        if frame.camera != Some(shot) {
+            frame.camera = Some(shot);
+            self.unsaved = true;
+        }
+    }
+
+    /// Move the camera by its top left handle and resize it by the bottom right one.
+    /// The shot center handle pans the camera of the selected frame.
+    /// Returns whether the camera is being dragged.
+    fn drag_camera(&mut self, handle: &RaylibHandle, pointer: &Pointer) -> bool {
+        let center = Vec2::new(BACKGROUND.0 as f32 / 2.0, BACKGROUND.1 as f32 / 2.0);
+        let position = self
+            .main_view
+            .to_canvas(pointer.position.into())
+            .sub(center);
+        // Zoomed out handles keep their screen size, so they can still be grabbed
+        let reach = CAMERA_HANDLE / pointer.zoom.min(1.0);
+        let mut camera = self.camera();
+
+        if handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            let corner = camera
+                .position
+                .add(Vec2::new(camera.size.0 as f32, camera.size.1 as f32));
+            let near = |point: Vec2| {
+                (position.x - point.x).abs() <= reach && (position.y - point.y).abs() <= reach
+            };
+
+            let shot = self.shot(self.selected_frame);
+
+            self.camera_drag = if near(shot.center) {
+                Some((CameraHandle::SHOT, shot.center.sub(position)))
+            } else if near(camera.position) {
+                Some((CameraHandle::MOVE, camera.position.sub(position)))
+            } else if near(corner) {
+                Some((CameraHandle::RESIZE, corner.sub(position)))
+            } else {
+                None
+            };
+        }
+
+        let (camera_handle, offset) = match self.camera_drag {
+            Some(drag) => drag,
+            None => return false,
+        };
+
+        match camera_handle {
+            CameraHandle::MOVE => {
+                let corner = position.add(offset);
+                camera.position = Vec2::new(corner.x.round(), corner.y.round());
+            }
+            CameraHandle::RESIZE => camera.resize_to(position.add(offset)),
+            CameraHandle::SHOT => {
+                let mut shot = self.shot(self.selected_frame);
+                shot.center = position.add(offset);
+                self.set_shot(shot);
+            }
+        }
+
+        camera.clamp_to(BACKGROUND);
+
+        // Miniatures are cropped again only once the camera is dropped
+        self.video_camera = camera_rect(&camera);
+
+        if handle.is_mouse_button_released(MouseButton::MOUSE_BUTTON_LEFT) {
+            self.camera_drag = None;
+
+            if camera_handle != CameraHandle::SHOT {
+                self.camera_changed = true;
+                self.unsaved = true;
+            }
+        }
+
+        true
+    }
+
+    /// Generate the poses of every frame in between two keyframes,
+    /// rendering again only the frames that have changed.
+    fn tween(&mut self, handle: &mut RaylibHandle, thread: &RaylibThread) {
+        self.should_tween = false;
+        self.frames[self.selected_frame].save_state();
+
+        let mut timeline = self.to_data(Vec2::zero());
+
+        for index in timeline.tween() {
+            let states = timeline.frames[index].states.drain(..);
+
+            for (anim, (_, state)) in self.frames[index].figure_animation.iter_mut().zip(states) {
+                anim.moved_edges = state;
+            }
+
+            self.render_frame(index, handle, thread);
+        }
+
+        self.frames[self.selected_frame].load_state();
+    }
+
+    pub fn push_frame(&mut self, handle: &mut RaylibHandle, thread: &RaylibThread) {
+        self.new_frame(self.frames.len(), handle, thread);
+    }
+
+    /// Insert a copy of the selected frame poses at index and select it
+    fn new_frame(&mut self, index: usize, handle: &mut RaylibHandle, thread: &RaylibThread) {
+        let previous = if index <= self.selected_frame {
+            self.selected_frame + 1
+        } else {
+            self.selected_frame
+        };
+
+        let frame = &mut self.frames[self.selected_frame];
         frame.save_state();
+        frame.is_selected = false;
 
-        frame.chage_figure_draw(true);
+        let mut new_frame = frame.clone();
+        new_frame.is_selected = true;
+        // New frames are drawn by hand until marked as keyframes
+        new_frame.keyframe = false;
+        new_frame.camera = None;
+        new_frame.hold = 1;
 
-        self.main_texture = new_frame.texture.clone();
-        self.selected_frame = self.frames.len();
-        self.frames.push(new_frame);
+        self.record(AnimationCommand::AddFrames {
+            index,
+            frames: vec![new_frame.state()],
+        });
+        self.frames.insert(index, new_frame);
+        self.render_frame(previous, handle, thread);
+        self.render_frame(index, handle, thread);
+
+        self.selected_frame = index;
+        self.frame_selection = index..index + 1;
+        self.frames[index].load_state();
+        self.should_tween = true;
+    }
+
+    /// Insert a copy of the selected frames after them
+    fn duplicate_frames(&mut self, handle: &mut RaylibHandle, thread: &RaylibThread) {
+        let selection = self.selection();
+        self.frames[self.selected_frame].save_state();
+
+        let frames: Vec<_> = self.frames[selection.clone()]
+            .iter()
+            .map(|frame| frame.state())
+            .collect();
+
+        self.insert_frames(selection.end, frames.clone(), handle, thread);
+        self.record(AnimationCommand::AddFrames {
+            index: selection.end,
+            frames,
+        });
+    }
+
+    /// Move selected frames to start at index
+    fn reorder_frames(&mut self, to: usize) {
+        let selection = self.selection();
+        let (from, count) = (selection.start, selection.len());
+
+        self.move_frames(from, count, to);
+        self.record(AnimationCommand::MoveFrames { from, count, to });
+    }
+
+    /// Move dragged frames to the carousel gap where the mouse is released.
+    /// Releasing without dragging selects the pressed frame alone.
+    fn drop_frames(&mut self, handle: &RaylibHandle) {
+        let (pressed, start) = match self.frame_caroussel.drag {
+            Some(drag) if handle.is_mouse_button_released(MouseButton::MOUSE_BUTTON_LEFT) => drag,
+            _ => return,
+        };
+        self.frame_caroussel.drag = None;
+
+        let mouse = handle.get_mouse_position();
+        if (mouse.x - start.x).abs() < FRAME_DRAG_DISTANCE {
+            if self.selection().len() > 1 {
+                self.select_frame(pressed);
+            }
+
+            return;
+        }
+
+        let selection = self.selection();
+        let gap = self.frame_caroussel.gap_at(mouse.x, self.frames.len());
+
+        // Dropping inside the dragged frames keeps them in place
+        if gap > selection.end {
+            self.reorder_frames(gap - selection.len());
+        } else if gap < selection.start {
+            self.reorder_frames(gap);
+        }
     }
 
     pub fn remove_frame(&mut self) {
-        self.frames.remove(self.selected_frame);
-        self.selected_frame = self.frames.len() - 1;
+        // Animation must have at least one frame
+        if self.frames.len() == 1 {
+            return;
+        }
+
+        let index = self.selected_frame;
+        self.frames[index].save_state();
+        self.record(AnimationCommand::RemoveFrame {
+            index,
+            frame: self.frames[index].state(),
+        });
+        self.delete_frame(index);
+    }
+
+    fn delete_frame(&mut self, index: usize) {
+        self.frames.remove(index);
+        self.selected_frame = index.min(self.frames.len() - 1);
 
         let mut frame = &mut self.frames[self.selected_frame];
         frame.is_selected = true;
-        self.main_texture = frame.texture.clone();
+        frame.load_state();
         self.frame_caroussel.value = 0.0;
+        self.should_tween = true;
+    }
+
+    /// Insert a frame from pose data and select it
+    fn insert_frame(
+        &mut self,
+        index: usize,
+        state: FrameState,
+        handle: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) {
+        let frame = &mut self.frames[self.selected_frame];
+        frame.is_selected = false;
+        frame.save_state();
+
+        self.frames.insert(index, Frame::from_state(state));
+        self.render_frame(index, handle, thread);
+
+        let frame = &mut self.frames[index];
+        frame.is_selected = true;
+        frame.load_state();
+        self.selected_frame = index;
+        self.should_tween = true;
+    }
+
+    /// Insert frames from pose data one after the other and select all of them
+    fn insert_frames(
+        &mut self,
+        index: usize,
+        states: Vec<FrameState>,
+        handle: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) {
+        let count = states.len();
+
+        for (offset, state) in states.into_iter().enumerate() {
+            self.insert_frame(index + offset, state, handle, thread);
+        }
+
+        self.frame_selection = index..index + count;
+    }
+
+    /// Move `count` frames starting at `from` so they start at `to`, keeping them selected
+    fn move_frames(&mut self, from: usize, count: usize, to: usize) {
+        self.frames[self.selected_frame].save_state();
+        let offset = self
+            .selected_frame
+            .checked_sub(from)
+            .filter(|offset| *offset < count)
+            .unwrap_or(0);
+
+        let moved: Vec<_> = self.frames.drain(from..from + count).collect();
+        self.frames.splice(to..to, moved);
+
+        let selected = to + offset;
+        for (i, frame) in self.frames.iter_mut().enumerate() {
+            frame.is_selected = i == selected;
+        }
+
+        self.selected_frame = selected;
+        self.frame_selection = to..to + count;
+        self.frames[selected].load_state();
+        self.should_tween = true;
+    }
+
+    fn record(&mut self, command: AnimationCommand) {
+        self.history.push(command);
+        self.unsaved = true;
+    }
+
+    /// Set figure states of a frame and select it
+    fn set_poses(
+        &mut self,
+        index: usize,
+        poses: Vec<HashMap<usize, (Vec2, Vec2)>>,
+        handle: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) {
+        self.select_frame(index);
+
+        for (anim, state) in self.frames[index].figure_animation.iter_mut().zip(poses) {
+            anim.moved_edges = state;
+        }
+
+        self.render_frame(index, handle, thread);
+        self.should_tween = true;
+    }
+
+    /// Revert last timeline operation
+    fn undo(&mut self, handle: &mut RaylibHandle, thread: &RaylibThread) {
+        let command = match self.history.undo() {
+            Some(command) => command.clone(),
+            None => return,
+        };
+        self.unsaved = true;
+
+        match command {
+            AnimationCommand::AddFrames { index, frames } => {
+                for _ in 0..frames.len() {
+                    self.delete_frame(index);
+                }
+            }
+            AnimationCommand::RemoveFrame { index, frame } => {
+                self.insert_frame(index, frame, handle, thread)
+            }
+            AnimationCommand::MoveFrames { from, count, to } => self.move_frames(to, count, from),
+            AnimationCommand::AddFigure {
+                frame,
+                global_index,
+                ..
+            } => {
+                self.select_frame(frame);
+                self.remove_figure(global_index);
+            }
+            AnimationCommand::ReplaceFigure {
+                global_index,
+                before: (figure, states),
+                ..
+            } => self.set_figure(global_index, figure, states),
+            AnimationCommand::Pose { frame, before, .. } => {
+                self.set_poses(frame, before, handle, thread)
+            }
+        }
+    }
+
+    /// Apply again last reverted timeline operation
+    fn redo(&mut self, handle: &mut RaylibHandle, thread: &RaylibThread) {
+        let command = match self.history.redo() {
+            Some(command) => command.clone(),
+            None => return,
+        };
+        self.unsaved = true;
+
+        match command {
+            AnimationCommand::AddFrames { index, frames } => {
+                self.insert_frames(index, frames, handle, thread)
+            }
+            AnimationCommand::RemoveFrame { index, .. } => self.delete_frame(index),
+            AnimationCommand::MoveFrames { from, count, to } => self.move_frames(from, count, to),
+            AnimationCommand::AddFigure {
+                frame,
+                global_index,
+                figure,
+            } => {
+                self.select_frame(frame);
+                self.add_figure(global_index, figure);
+            }
+            AnimationCommand::ReplaceFigure {
+                global_index,
+                after: (figure, states),
+                ..
+            } => self.set_figure(global_index, figure, states),
+            AnimationCommand::Pose { frame, after, .. } => {
+                self.set_poses(frame, after, handle, thread)
+            }
+        }
     }
 
     fn play(&mut self, handle: &mut RaylibHandle, thread: &RaylibThread) {
         let current_time = handle.get_time();
         let frame_time = current_time - self.previous_time;
-        let wait_time: f64 = (1.0 / self.framerate as f64) - frame_time;
+        let hold = self.frames[self.selected_frame].hold as f64;
+        let wait_time: f64 = (hold / self.framerate as f64) - frame_time;
 
         if wait_time <= 0.0 {
             let index = if self.selected_frame == self.frames.len() - 1 {
@@ -817,161 +2158,146 @@ impl Animation {
     }
 
     // External files:
-    /// Save animation into a file with raw format
-    fn save(&mut self, filename: &str) {
+    /// Save animation into a file with selected encoding
+    fn save(&mut self, filename: &str) -> Result<()> {
         let extension = match self.save_format {
             FileEncoding::RAW => "var",
             FileEncoding::GZIP => "vag",
             FileEncoding::ZLIB => "vaz",
         };
 
-        let path = FileDialog::new()
-            .set_filename(&(filename.to_owned() + "." + extension))
-            .add_filter("Vetor Animation", &["var", "vag", "vaz"])
-            .show_save_single_file()
-            .expect("Cannot save file");
-
-        if path.is_none() {
-            return;
-        }
-
-        let mut file = ZlibEncoder::new(vec![], Compression::default());
-
-        // Save Figures
-        for (i, figRef) in self.figures.iter().enumerate() {
-            let figure = figRef.try_borrow().ok().unwrap();
-            let points = archives::figure_to_raw(figure.clone());
-
-            for point in points {
-                file.write(
-                    format!(
-                        "{},{},{},{},{}\n",
-                        point.typ, point.x, point.y, point.parent, point.index
-                    )
-                    .as_bytes(),
-                )
-                .expect("Cannot write points to file");
-            }
+        let path = dialog::picked_path(
+            FileDialog::new()
+                .set_filename(&(filename.to_owned() + "." + extension))
+                .add_filter("Vetor Animation", &["var", "vag", "vaz"])
+                .show_save_single_file(),
+        )?;
 
-            // Add interssection only in between figures
-            if i != self.figures.len() - 1 {
-                file.write("^\n".as_bytes()).ok();
-            };
-        }
+        let path = match path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
 
-        for (frame_index, frame) in self.frames.iter_mut().enumerate() {
-            file.write(format!("@Frame {}\n", frame_index).as_bytes())
-                .ok();
-            for figState in &mut frame.figure_animation {
-                file.write(format!("^{}\n", figState.global_index).as_bytes())
-                    .ok();
+        let center = rvec2(BACKGROUND.0 / 2, BACKGROUND.1 / 2).into();
+        let raw = archives::animation_to_raw(&self.to_data(center));
+        archives::write(&path, &raw, self.save_format)?;
+        self.path = Some(path);
+        self.unsaved = false;
 
-                figState
-                    .figure
-                    .try_borrow_mut()
-                    .ok()
-                    .unwrap()
-                    .load_state(figState.moved_edges.clone());
-                figState.moved_edges = figState.figure.try_borrow().ok().unwrap().scan();
-                let mut moved_edges: Vec<_> = figState.moved_edges.iter().collect();
-
-                moved_edges.sort_by(|a, b| {
-                    if a.0 == b.0 {
-                        return Ordering::Equal;
-                    } else if a.0 < b.0 {
-                        return Ordering::Less;
-                    }
+        Ok(())
+    }
 
-                    Ordering::Greater
-                });
+    /// Animation has changes not written to a file
+    pub fn unsaved(&self) -> bool {
+        self.unsaved
+    }
 
-                for (_, edge) in moved_edges {
-                    file.write(
-                        format!("{},{},{},{}\n", edge.0.x, edge.0.y, edge.1.x, edge.1.y).as_bytes(),
-                    )
-                    .ok();
-                }
-            }
-        }
+    /// File of the animation, `None` until it is opened or saved
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
 
-        fs::write(path.unwrap(), file.finish().unwrap()).ok();
+    /// Show the save dialog, `saving` is true until it is closed
+    pub fn show_save_dialog(&mut self) {
+        self.save_animation.activated = true;
     }
 
-    /// Load animation scenes from raw file
-    pub fn load(
-        path: &str,
-        handle: &mut RaylibHandle,
-        thread: &RaylibThread,
-        encoding: FileEncoding,
-    ) -> Animation {
-        let buffer = fs::read(path).ok().unwrap();
-        let file = match encoding {
-            FileEncoding::RAW => String::from_utf8(buffer).unwrap(),
-            FileEncoding::GZIP => {
-                let mut decoder = GzDecoder::new(buffer.as_slice());
-                let mut raw = String::new();
-                decoder.read_to_string(&mut raw);
-                raw
-            }
-            FileEncoding::ZLIB => {
-                let mut decoder = ZlibDecoder::new(buffer.as_slice());
-                let mut raw = String::new();
-                decoder.read_to_string(&mut raw);
-                raw
-            }
-        };
+    pub fn saving(&self) -> bool {
+        self.save_animation.activated
+    }
 
-        let split: Vec<_> = file.split("@Frame").collect();
-        let (figs, frames) = (split[0], &split[1..]);
-        let mut animation = Animation::new(handle, thread);
+    /// Map figures and frame states relative to given origin
+    fn to_data(&mut self, origin: Vec2) -> AnimationData {
+        self.frames[self.selected_frame].save_state();
 
-        animation.figures = vec![];
-        animation.frames[0].figure_animation = vec![];
+        let mut header = self.metadata.clone();
+        header.canvas = BACKGROUND;
+        header.fps = self.framerate;
+        header.camera = self.camera();
 
-        for fig in figs.split("^\n") {
-            animation
+        AnimationData {
+            header,
+            figures: self
                 .figures
-                .push(Rc::new(RefCell::new(archives::raw_to_figure(fig))));
+                .iter()
+                .map(|figure| figure.try_borrow().ok().unwrap().clone())
+                .collect(),
+            frames: self
+                .frames
+                .iter()
+                .map(|frame| FrameData {
+                    keyframe: frame.keyframe,
+                    easing: frame.easing,
+                    camera: frame.camera,
+                    hold: frame.hold,
+                    states: frame
+                        .figure_animation
+                        .iter()
+                        .map(|anim| {
+                            let state = anim
+                                .moved_edges
+                                .iter()
+                                .map(|(i, (start, end))| (*i, (start.sub(origin), end.sub(origin))))
+                                .collect();
+
+                            (anim.global_index, state)
+                        })
+                        .collect(),
+                })
+                .collect(),
         }
+    }
 
-        let mut last_frame = animation.frames.first_mut().unwrap();
-
-        let center = rvec2(BACKGROUND.0 / 2, BACKGROUND.1 / 2);
-
-        // Map and mount each frame
-        for (i, frameStr) in frames.iter().enumerate() {
-            last_frame.figure_animation = vec![];
-
-            // Map and mount each Figure in figure_animation
-            for state in frameStr.split_once("\n").unwrap().1.split("^") {
-                if state.trim().len() == 0 {
-                    continue;
-                }
-
-                let mut lines = state.lines();
-                let index = lines.next().unwrap().parse::<usize>().ok().unwrap();
-                let mut moved_edges = HashMap::new();
-
-                // Map and moutn each figure edge state
-                for line in lines {
-                    let edge: Vec<_> = line.split(",").collect();
+    /// Replace current animation with an animation file
+    fn open(&mut self, handle: &mut RaylibHandle, thread: &RaylibThread) -> Result<()> {
+        let path = dialog::picked_path(
+            FileDialog::new()
+                .set_location("./src/assets/animations")
+                .add_filter("Vetor Animation", &["var", "vag", "vaz"])
+                .show_open_single_file(),
+        )?;
+
+        let path = match path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
 
-                    if edge.len() < 4 {
-                        continue;
-                    }
+        *self = Animation::load(&path, handle, thread)?;
+        self.unsaved = false;
 
-                    let start = rvec2(
-                        edge[0].parse::<i32>().unwrap(),
-                        edge[1].parse::<i32>().unwrap(),
-                    );
-                    let end = rvec2(
-                        edge[2].parse::<i32>().unwrap(),
-                        edge[3].parse::<i32>().unwrap(),
-                    );
+        Ok(())
+    }
 
-                    moved_edges.insert(moved_edges.len() as usize, (start, end));
-                }
+    /// Load animation scenes from file
+    pub fn load(path: &str, handle: &mut RaylibHandle, thread: &RaylibThread) -> Result<Animation> {
+        let (raw, encoding) = archives::read(path)?;
+        let data = archives::raw_to_animation(&raw)?;
+        let mut animation = Animation::new(handle, thread);
+        animation.save_format = encoding;
+        animation.path = Some(path.to_owned());
+
+        animation.figures = data
+            .figures
+            .into_iter()
+            .map(|figure| Rc::new(RefCell::new(figure)))
+            .collect();
+        animation.framerate = data.header.fps;
+        animation.video_camera = camera_rect(&data.header.camera);
+        animation.metadata = data.header;
+
+        let center: Vec2 = rvec2(BACKGROUND.0 / 2, BACKGROUND.1 / 2).into();
+        animation.frames = vec![];
+
+        // Map and mount each frame, only pose data is kept
+        for frame_data in data.frames {
+            let mut frame = Frame::new();
+            frame.keyframe = frame_data.keyframe;
+            frame.easing = frame_data.easing;
+            frame.camera = frame_data.camera;
+            frame.hold = frame_data.hold;
 
+            // Map and mount each Figure in figure_animation
+            for (index, mut moved_edges) in frame_data.states {
                 let mut figure = animation.figures[index].try_borrow_mut().ok().unwrap();
 
                 for (_, e) in moved_edges.iter_mut() {
@@ -981,89 +2307,272 @@ impl Animation {
 
                 figure.load_state(moved_edges.clone());
 
-                last_frame.figure_animation.push(FigureAnimation {
+                frame.figure_animation.push(FigureAnimation {
                     global_index: index,
-                    local_index: last_frame.figure_animation.len(),
+                    local_index: frame.figure_animation.len(),
                     figure: animation.figures[index].clone(),
                     moved_edges,
                 });
             }
 
-            last_frame.save_state();
-            last_frame.chage_figure_draw(false);
-            last_frame.render_screen(&mut handle.begin_drawing(thread), thread);
-            last_frame.render_miniature(
-                handle,
-                thread,
-                animation.frame_caroussel.display_width,
-                animation.frame_caroussel.display_height,
-                animation.video_camera,
-            );
-            last_frame.chage_figure_draw(true);
-            last_frame.render_screen(&mut handle.begin_drawing(thread), thread);
-
-            if i < frames.len() - 1 {
-                let texture = handle
-                    .load_render_texture(
-                        thread,
-                        last_frame.texture.try_borrow().ok().unwrap().width() as u32,
-                        last_frame.texture.try_borrow().ok().unwrap().height() as u32,
-                    )
-                    .ok()
-                    .unwrap();
+            frame.save_state();
+            animation.frames.push(frame);
+        }
+
+        if animation.frames.is_empty() {
+            animation.frames.push(Frame::new());
+        }
 
-                let mut new_frame = last_frame.clone(texture);
-                last_frame.is_selected = false;
+        animation.selected_frame = animation.frames.len() - 1;
+        animation.frames[animation.selected_frame].is_selected = true;
+        animation.render_miniatures(handle, thread);
 
-                animation.main_texture = new_frame.texture.clone();
-                animation.selected_frame = animation.frames.len();
-                animation.frames.push(new_frame);
-                last_frame = animation.frames.last_mut().unwrap();
-            }
+        Ok(animation)
+    }
+
+    pub fn export(
+        &mut self,
+        draw_handle: &mut RaylibDrawHandle,
+        thread: &RaylibThread,
+        file: &str,
+        format: ExportFormat,
+    ) -> Result<()> {
+        let (filter, extension) = match format {
+            ExportFormat::GIF => ("Video", "gif"),
+            ExportFormat::MP4 => ("Video", "mp4"),
+            ExportFormat::PNG | ExportFormat::SPRITESHEET => ("Image", "png"),
+            ExportFormat::SVG | ExportFormat::SVG_ANIMATED => ("Vector image", "svg"),
+        };
+
+        let path = dialog::picked_path(
+            FileDialog::new()
+                .set_filename(&format!("{}.{}", file, extension))
+                .add_filter(filter, &[extension])
+                .show_save_single_file(),
+        )?;
+
+        let path = match path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        self.frames[self.selected_frame].save_state();
+
+        let result = match format {
+            ExportFormat::GIF => self.export_gif(draw_handle, thread, &path),
+            ExportFormat::MP4 => self.export_video(draw_handle, thread, &path),
+            ExportFormat::PNG => self.export_png_sequence(draw_handle, thread, &path),
+            ExportFormat::SPRITESHEET => self.export_sprite_sheet(draw_handle, thread, &path),
+            ExportFormat::SVG | ExportFormat::SVG_ANIMATED => self.export_svg(&path, format),
+        };
+
+        // Exported frames are drawn on the shared canvas with their own poses
+        self.frames[self.selected_frame].load_state();
+
+        result
+    }
+
+    /// Write figures inside the video camera as vector image, without rasterizing them
+    fn export_svg(&mut self, path: &str, format: ExportFormat) -> Result<()> {
+        let center = rvec2(BACKGROUND.0 / 2, BACKGROUND.1 / 2);
+        let data = self.to_data(center.into());
+
+        let content = match format {
+            ExportFormat::SVG_ANIMATED => svg::animation(&data, Color::RAYWHITE.into()),
+            _ => svg::frame(&data, self.selected_frame, Color::RAYWHITE.into()),
+        };
+
+        fs::write(path, content).map_err(|error| VetorError::io(path, error))
+    }
+
+    /// Write `<name>_0000.png`, `<name>_0001.png`, ... next to the chosen path
+    fn export_png_sequence(
+        &mut self,
+        draw_handle: &mut RaylibDrawHandle,
+        thread: &RaylibThread,
+        path: &str,
+    ) -> Result<()> {
+        let shots = self.shots();
+
+        for (index, shot) in shots.into_iter().enumerate() {
+            png::write(
+                &png::sequence_path(path, index),
+                self.video_camera.width as u32,
+                self.video_camera.height as u32,
+                &self.camera_pixels(draw_handle, thread, index, shot),
+            )?;
         }
 
-        animation
+        Ok(())
     }
 
-    pub fn export(&mut self, file: &str, format: &str) {
-        let filename = &format!("{}.{}", file, format);
-        let path = FileDialog::new()
-            .set_filename(filename)
-            .add_filter("Video", &["gif", "mp4"])
-            .show_save_single_file()
-            .expect("Cannot save file");
+    /// Pack every frame in one image, described by a JSON atlas with the same name
+    fn export_sprite_sheet(
+        &mut self,
+        draw_handle: &mut RaylibDrawHandle,
+        thread: &RaylibThread,
+        path: &str,
+    ) -> Result<()> {
+        let mut sheet = SpriteSheet::new(
+            self.video_camera.width as u32,
+            self.video_camera.height as u32,
+            self.frames.len(),
+        )?;
+
+        let shots = self.shots();
+
+        for (index, shot) in shots.into_iter().enumerate() {
+            sheet.insert(index, &self.camera_pixels(draw_handle, thread, index, shot));
+            sheet.holds[index] = self.frames[index].hold;
+        }
 
-        if path.is_none() {
-            return;
+        sheet.write(path, self.framerate)
+    }
+
+    /// Encode frames in-process, without depending on external programs
+    fn export_gif(
+        &mut self,
+        draw_handle: &mut RaylibDrawHandle,
+        thread: &RaylibThread,
+        path: &str,
+    ) -> Result<()> {
+        let mut encoder = GifEncoder::create(
+            path,
+            self.video_camera.width as u16,
+            self.video_camera.height as u16,
+            self.framerate,
+            self.gif_loops,
+        )?;
+
+        let shots = self.shots();
+
+        for (index, shot) in shots.into_iter().enumerate() {
+            let hold = self.frames[index].hold;
+            encoder.push(
+                &mut self.camera_pixels(draw_handle, thread, index, shot),
+                hold,
+            )?;
         }
 
-        fs::remove_file(path.clone().unwrap()).err();
+        Ok(())
+    }
 
+    /// Pipe raw frames into ffmpeg, which must be available on PATH
+    fn export_video(
+        &mut self,
+        draw_handle: &mut RaylibDrawHandle,
+        thread: &RaylibThread,
+        path: &str,
+    ) -> Result<()> {
         let mut ffmpeg = Command::new("ffmpeg")
+            .args(["-y", "-f", "rawvideo", "-pixel_format", "rgba"])
+            .args([
+                "-video_size",
+                &format!("{}x{}", self.video_camera.width, self.video_camera.height),
+            ])
             .args(["-framerate", &self.framerate.to_string(), "-i", "-"])
-            .args([path.unwrap().to_str().unwrap()])
+            .args(["-pix_fmt", "yuv420p", path])
             .stdin(Stdio::piped())
             .spawn()
-            .expect("Cannot spawn ffmpeg command");
+            .map_err(|error| VetorError::io("ffmpeg", error))?;
 
-        let stdin = ffmpeg.stdin.as_mut().unwrap();
+        let mut stdin = ffmpeg.stdin.take().unwrap();
 
-        // NOTE: I don't like the ideia of saving image into a file,
-        // but this works now and can it be refactored latter.
-        let tmp_file = "tmp.png";
+        let shots = self.shots();
 
-        for frame in &mut self.frames {
-            let texture = frame.texture.try_borrow().ok().unwrap();
-            let mut image = texture.load_image().unwrap();
-            image.flip_vertical();
-            image.crop(self.video_camera);
-            image.export_image(tmp_file);
-            stdin.write_all(fs::read(tmp_file).ok().unwrap().as_slice());
+        // Constant framerate, held frames are repeated
+        for (index, shot) in shots.into_iter().enumerate() {
+            let pixels = self.camera_pixels(draw_handle, thread, index, shot);
+
+            for _ in 0..self.frames[index].hold {
+                stdin
+                    .write_all(&pixels)
+                    .map_err(|error| VetorError::io("ffmpeg", error))?;
+            }
         }
 
-        fs::remove_file(tmp_file);
+        drop(stdin);
+        let status = ffmpeg
+            .wait()
+            .map_err(|error| VetorError::io("ffmpeg", error))?;
 
-        stdin.flush().expect("Cannot flush ffmpeg stdin");
-        ffmpeg.wait();
+        if !status.success() {
+            return Err(VetorError::ENCODE {
+                message: format!("ffmpeg exited with {}", status),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// RGBA pixels of a frame screen seen by the video camera.
+    /// The frame is drawn on the canvas first, figure states of the selected
+    /// frame must be loaded again after exporting.
+    /// The screen is drawn panned, zoomed and rotated by the shot into a texture
+    /// of the camera size, so only the camera pixels are read back.
+    fn camera_pixels(
+        &mut self,
+        draw_handle: &mut RaylibDrawHandle,
+        thread: &RaylibThread,
+        index: usize,
+        shot: Shot,
+    ) -> Vec<u8> {
+        let frame = &mut self.frames[index];
+        frame.load_state();
+        frame.chage_figure_draw(false);
+        frame.render_screen(draw_handle, thread, &mut self.canvas, &[]);
+        frame.chage_figure_draw(true);
+
+        let texture = &self.canvas;
+        let mut target = draw_handle
+            .load_render_texture(
+                thread,
+                self.video_camera.width as u32,
+                self.video_camera.height as u32,
+            )
+            .ok()
+            .unwrap();
+
+        {
+            let mut draw_texture = draw_handle.begin_texture_mode(thread, &mut target);
+            draw_texture.clear_background(Color::RAYWHITE);
+
+            let mut draw_camera = draw_texture.begin_mode2D(Camera2D {
+                offset: rvec2(
+                    self.video_camera.width / 2.0,
+                    self.video_camera.height / 2.0,
+                ),
+                target: rvec2(BACKGROUND.0 / 2, BACKGROUND.1 / 2).add(shot.center.into()),
+                rotation: -shot.rotation,
+                zoom: shot.zoom,
+            });
+
+            // Render textures are stored upside down
+            draw_camera.draw_texture_rec(
+                texture.texture(),
+                rrect(0, 0, texture.texture.width, -texture.texture.height),
+                rvec2(0, 0),
+                Color::WHITE,
+            );
+        }
+
+        let mut image = target.texture().load_image().unwrap();
+        image.flip_vertical();
+
+        image
+            .get_image_data()
+            .iter()
+            .flat_map(|color| [color.r, color.g, color.b, color.a])
+            .collect()
     }
 }
+
+/// Camera rectangle on the canvas texture
+fn camera_rect(camera: &Camera) -> Rectangle {
+    rrect(
+        camera.position.x + BACKGROUND.0 as f32 / 2.0,
+        camera.position.y + BACKGROUND.1 as f32 / 2.0,
+        camera.size.0,
+        camera.size.1,
+    )
+}
@@ -1,6 +1,14 @@
-use super::{util::button::Button, BACKGROUND};
+use super::{
+    history::History,
+    util::{button::Button, canvas::CanvasView, dialog},
+    BACKGROUND,
+};
 use crate::{
-    archives::{self, FileEncoding},
+    archives::{
+        self,
+        error::{Result, VetorError},
+        FileEncoding,
+    },
     cstr,
     figure::{edge::*, *},
     icons::VetorIcons,
@@ -8,14 +16,66 @@ use crate::{
 };
 use native_dialog::FileDialog;
 use raylib::{prelude::*, texture::RenderTexture2D};
-use std::{ffi::CString, fs, path::Path};
+use std::{collections::HashMap, ffi::CString, fs, path::Path};
+use vetor_core::export::svg;
+
+/// Start and end points of every edge, by index
+type Points = HashMap<usize, (Vec2, Vec2)>;
+/// Stroke color and thickness of an edge
+type Stroke = (Rgba, f32);
+
+/// Edit actions keep only what they changed, to be applied again or reverted.
+/// Actions changing the tree structure are applied again on redo, but keep the
+/// tree they replaced to be undone, as sorting may move any edge index.
+#[derive(Debug, Clone)]
+pub enum EditCommand {
+    INSERT {
+        edge: Edge,
+        previous: Vec<Edge>,
+    },
+    COPY {
+        edge: Edge,
+        previous: Vec<Edge>,
+    },
+    DELETE {
+        index: usize,
+        previous: Vec<Edge>,
+    },
+    DIVIDE {
+        index: usize,
+        previous: Vec<Edge>,
+    },
+    /// Toggling the type twice gives back the same edge
+    TOGGLE_TYPE {
+        index: usize,
+    },
+    DRAW_MODE {
+        index: usize,
+        before: EdgeDrawMode,
+        after: EdgeDrawMode,
+    },
+    /// Points dragged without any tool selected
+    MOVE {
+        before: Points,
+        after: Points,
+    },
+    /// Stroke color or thickness changed on the properties panel
+    STYLE {
+        index: usize,
+        before: Stroke,
+        after: Stroke,
+    },
+    OPEN {
+        before: Vec<Edge>,
+        after: Vec<Edge>,
+    },
+}
 
 pub struct Edit {
     figure: Figure,
     texture: RenderTexture2D,
-    main_position: Vector2,
-    main_scroll: Vector2,
-    previous_mouse_pos: Vector2,
+    main_view: CanvasView,
+    fit_figure: Button,
 
     sidebar_width: f32,
     start: Vector2,
@@ -30,9 +90,26 @@ pub struct Edit {
     circle_fill: Button,
     root: Button,
     format: Button,
+    undo: Button,
+    redo: Button,
+
+    history: History<EditCommand>,
+    drag_start: Option<Points>,
+    dragging: bool,
+    /// Stroke of the focused edge when the mouse was pressed
+    style_start: Option<(usize, Stroke)>,
+    /// Last pressed edge, shown on the properties panel
+    focused_edge: Option<usize>,
 
     save_figure: Button,
+    open_figure: Button,
+    /// Send the figure to the animation tab
+    send_figure: Button,
     encoding: FileEncoding,
+    /// File the figure was opened from or last saved to
+    path: Option<String>,
+    unsaved: bool,
+    error: Option<String>,
 }
 
 impl Edit {
@@ -50,16 +127,19 @@ impl Edit {
         let circle_fill = Button::dynamic_new(3, 1, start, sidebar_width);
         let root = Button::dynamic_new(4, 0, start, sidebar_width);
         let format = Button::dynamic_new(4, 1, start, sidebar_width);
+        let undo = Button::dynamic_new(5, 0, start, sidebar_width);
+        let redo = Button::dynamic_new(5, 1, start, sidebar_width);
 
-        let screen_center = rvec2(
-            handle.get_screen_width() / 2,
-            handle.get_screen_height() / 2,
+        let main_rec = rrect(
+            sidebar_width,
+            start.y,
+            handle.get_screen_width() as f32 - sidebar_width,
+            handle.get_screen_height() as f32 - start.y,
         );
+        let canvas_center = rvec2(BACKGROUND.0 / 2, BACKGROUND.1 / 2);
 
-        let texture_center = rvec2(
-            -(BACKGROUND.0 as i32 / 2) + (screen_center.x) as i32,
-            -(BACKGROUND.1 as i32 / 2) + screen_center.y as i32,
-        );
+        let mut fit_figure = Button::new(Vector2::zero());
+        fit_figure.text = Some(cstr!("Figura"));
 
         Edit {
             btn_pressed: false,
@@ -73,32 +153,84 @@ impl Edit {
             circle_fill,
             root,
             format,
+            undo,
+            redo,
+            history: History::new(100),
+            drag_start: None,
+            dragging: false,
+            style_start: None,
+            focused_edge: None,
             start,
             sidebar_width,
             figure: Figure::new(vec![Edge::new(
-                -texture_center.sub(screen_center),
-                -texture_center.sub(screen_center).sub(rvec2(0, 100)),
+                canvas_center.into(),
+                canvas_center.sub(rvec2(0, 100)).into(),
                 -1,
                 1,
             )]),
             texture,
-            main_position: rvec2(sidebar_width, start.y),
-            previous_mouse_pos: handle.get_mouse_position(),
-            main_scroll: texture_center,
-            save_figure: Button::new(start.add(rvec2(5, 260))),
+            main_view: CanvasView::new(BACKGROUND, main_rec),
+            fit_figure,
+            save_figure: Button::new(start.add(rvec2(5, 310))),
+            open_figure: Button::new(start.add(rvec2(5, 345))),
+            send_figure: Button::new(start.add(rvec2(5, 380))),
             encoding: FileEncoding::ZLIB,
+            path: None,
+            unsaved: false,
+            error: None,
         }
     }
 
     pub fn update(&mut self, handle: &RaylibHandle) {
-        if self.save_figure.activated {
+        if self.save_figure.activated || self.error.is_some() {
+            return;
+        }
+
+        let control = handle.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || handle.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        let shift = handle.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+            || handle.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+
+        if self.undo.activated || (control && !shift && handle.is_key_pressed(KeyboardKey::KEY_Z)) {
+            self.undo();
+        }
+
+        if self.redo.activated || (control && shift && handle.is_key_pressed(KeyboardKey::KEY_Z)) {
+            self.redo();
+        }
+
+        if self.fit_figure.activated || (!control && handle.is_key_pressed(KeyboardKey::KEY_F)) {
+            self.main_view.fit(self.figure.rect());
+        }
+
+        if self.main_view.reset_zoom.activated
+            || (control && handle.is_key_pressed(KeyboardKey::KEY_ZERO))
+        {
+            self.main_view.reset();
+        }
+
+        if self.open_figure.activated {
+            self.open_figure.activated = false;
+
+            if let Err(error) = self.open() {
+                self.error = Some(error.to_string());
+            }
+
             return;
         }
 
+        if handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            self.drag_start = Some(self.figure.scan());
+            self.style_start = self
+                .focused_edge
+                .filter(|index| *index < self.figure.tree.len())
+                .map(|index| (index, self.stroke(index)));
+        }
+
         if self.insert.activated || self.circle.activated {
             match self.figure.tmp_edge {
                 Some(mut edge) => {
-                    edge.end = handle.get_mouse_position();
+                    edge.end = self.main_view.to_canvas(handle.get_mouse_position());
                     edge.width = edge.start.distance_to(edge.end);
                     edge.fixed_angle = edge.end.angle_to(edge.start);
                     edge.format = if self.circle.activated {
@@ -108,7 +240,9 @@ impl Edit {
                     };
 
                     if handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+                        let previous = self.figure.tree.clone();
                         self.figure.insert(edge);
+                        self.record(EditCommand::INSERT { edge, previous });
                         self.figure.tmp_edge = None;
                         self.figure.selected = None;
                         self.figure.pressed = false;
@@ -139,7 +273,12 @@ impl Edit {
                             index as isize
                         };
 
-                        self.figure.tmp_edge = Some(Edge::new(end, end, parent, 1));
+                        // New edges keep the stroke of the pressed one
+                        let mut edge = Edge::new(end, end, parent, 1);
+                        edge.color = pressed.color;
+                        edge.thickness = pressed.thickness;
+
+                        self.figure.tmp_edge = Some(edge);
                         self.figure.draw_option.point = false;
                         self.figure.presset_root = false;
                         self.figure.should_update = false;
@@ -158,7 +297,7 @@ impl Edit {
             match self.figure.tmp_edge {
                 Some(mut edge) => {
                     // Edge will move with mouse before insert
-                    edge.start = handle.get_mouse_position();
+                    edge.start = self.main_view.to_canvas(handle.get_mouse_position());
                     edge.end = vector2_rotate(edge.width, edge.fixed_angle).add(edge.start);
                     edge.update_angle();
                     edge.moved_angle = 0.0;
@@ -175,7 +314,9 @@ impl Edit {
                         let index = self.figure.selected.unwrap();
 
                         edge.parent = index as isize;
+                        let previous = self.figure.tree.clone();
                         self.figure.insert(edge);
+                        self.record(EditCommand::COPY { edge, previous });
                         self.figure.tmp_edge = None;
                         self.figure.selected = None;
                         self.figure.pressed = false;
@@ -208,7 +349,10 @@ impl Edit {
         if self.figure.pressed {
             if self.toggle_type.activated {
                 match self.figure.selected {
-                    Some(index) => self.figure.toggle_type(index),
+                    Some(index) => {
+                        self.figure.toggle_type(index);
+                        self.record(EditCommand::TOGGLE_TYPE { index });
+                    }
                     _ => (),
                 }
 
@@ -218,7 +362,11 @@ impl Edit {
 
             if self.divide.activated {
                 match self.figure.selected {
-                    Some(index) => self.figure.divide(index),
+                    Some(index) => {
+                        let previous = self.figure.tree.clone();
+                        self.figure.divide(index);
+                        self.record(EditCommand::DIVIDE { index, previous });
+                    }
                     _ => (),
                 }
 
@@ -228,7 +376,11 @@ impl Edit {
 
             if self.delete.activated {
                 match self.figure.selected {
-                    Some(index) => self.figure.delete(index),
+                    Some(index) => {
+                        let previous = self.figure.tree.clone();
+                        self.figure.delete(index);
+                        self.record(EditCommand::DELETE { index, previous });
+                    }
                     _ => (),
                 }
 
@@ -240,6 +392,7 @@ impl Edit {
                 match self.figure.selected {
                     Some(index) => {
                         let edge = self.figure.get_mut(index);
+                        let before = edge.draw_mode;
 
                         if edge.format == EdgeFormat::LINE {
                             edge.draw_mode = if edge.draw_mode == EdgeDrawMode::DEFAULT {
@@ -247,6 +400,12 @@ impl Edit {
                             } else {
                                 EdgeDrawMode::DEFAULT
                             };
+                            let after = edge.draw_mode;
+                            self.record(EditCommand::DRAW_MODE {
+                                index,
+                                before,
+                                after,
+                            });
                         }
                     }
                     _ => (),
@@ -260,6 +419,7 @@ impl Edit {
                 match self.figure.selected {
                     Some(index) => {
                         let edge = self.figure.get_mut(index);
+                        let before = edge.draw_mode;
 
                         if edge.format == EdgeFormat::CIRCLE {
                             edge.draw_mode = if edge.draw_mode == EdgeDrawMode::DEFAULT {
@@ -268,7 +428,13 @@ impl Edit {
                                 EdgeDrawMode::CIRCLE_FULL
                             } else {
                                 EdgeDrawMode::DEFAULT
-                            }
+                            };
+                            let after = edge.draw_mode;
+                            self.record(EditCommand::DRAW_MODE {
+                                index,
+                                before,
+                                after,
+                            });
                         }
                     }
                     _ => (),
@@ -280,7 +446,47 @@ impl Edit {
         }
 
         self.figure
-            .update(handle, Vector2::zero().add(self.main_scroll));
+            .update(&self.main_view.pointer(handle), self.main_view.origin());
+
+        if self.figure.selected.is_some() && !self.btn_pressed {
+            self.focused_edge = self.figure.selected;
+        }
+
+        // Points dragged without any tool selected
+        if self.figure.pressed && !self.btn_pressed && self.drag_start.is_some() {
+            self.dragging = true;
+        }
+
+        if handle.is_mouse_button_released(MouseButton::MOUSE_BUTTON_LEFT) {
+            let style_start = self.style_start.take();
+
+            match self.drag_start.take() {
+                Some(before) if self.dragging => {
+                    let after = self.figure.scan();
+
+                    if before != after {
+                        self.record(EditCommand::MOVE { before, after });
+                    }
+                }
+                Some(_) => match style_start {
+                    Some((index, before)) if index < self.figure.tree.len() => {
+                        let after = self.stroke(index);
+
+                        if before != after {
+                            self.record(EditCommand::STYLE {
+                                index,
+                                before,
+                                after,
+                            });
+                        }
+                    }
+                    _ => (),
+                },
+                None => (),
+            }
+
+            self.dragging = false;
+        }
 
         if !self.figure.pressed && handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
             self.figure.should_update = false;
@@ -291,78 +497,138 @@ impl Edit {
         }
     }
 
-    pub fn draw(&mut self, handle: &mut RaylibDrawHandle, thread: &RaylibThread) {
-        let height = handle.get_screen_height() - self.start.y as i32;
-        // ===== Drawing figure =====
-        {
-            let mut draw_texture = handle.begin_texture_mode(thread, &mut self.texture);
-            self.figure.draw(&mut draw_texture);
-        }
-        // ===== Drawing main Texture Screen =====
-        {
-            let mut texture_rec = rrect(
-                0,
-                0,
-                self.texture.texture.width,
-                self.texture.texture.height,
-            );
+    fn stroke(&self, index: usize) -> Stroke {
+        let edge = self.figure.get(index);
+        (edge.color, edge.thickness)
+    }
 
-            let main_rec = rrect(
-                self.main_position.x,
-                self.main_position.y,
-                handle.get_screen_width() as f32 - self.main_position.x,
-                handle.get_screen_height() as f32 - self.main_position.y,
-            );
+    /// Register an already applied action
+    fn record(&mut self, command: EditCommand) {
+        // Edges are sorted again when the tree changes
+        if !matches!(
+            command,
+            EditCommand::MOVE { .. } | EditCommand::STYLE { .. }
+        ) {
+            self.focused_edge = None;
+        }
 
-            let (scissor_rec, main_scroll) =
-                handle.gui_scroll_panel(main_rec, None, texture_rec, self.main_scroll);
-            self.main_scroll = main_scroll;
+        self.history.push(command);
+        self.unsaved = true;
+        // Avoid registering the same change as a point drag
+        self.drag_start = None;
+        self.style_start = None;
+        self.dragging = false;
+    }
 
-            // Draw main screen texture
-            let mut scissor = handle.begin_scissor_mode(
-                scissor_rec.x as i32,
-                scissor_rec.y as i32,
-                scissor_rec.width as i32,
-                scissor_rec.height as i32,
-            );
+    /// Apply a recorded action again, or revert it
+    fn apply(&mut self, command: EditCommand, revert: bool) {
+        match command {
+            EditCommand::INSERT { edge, previous } | EditCommand::COPY { edge, previous } => {
+                if revert {
+                    self.figure.restore(previous);
+                } else {
+                    self.figure.insert(edge);
+                }
+            }
+            EditCommand::DELETE { index, previous } => {
+                if revert {
+                    self.figure.restore(previous);
+                } else {
+                    self.figure.delete(index);
+                }
+            }
+            EditCommand::DIVIDE { index, previous } => {
+                if revert {
+                    self.figure.restore(previous);
+                } else {
+                    self.figure.divide(index);
+                }
+            }
+            EditCommand::TOGGLE_TYPE { index } => self.figure.toggle_type(index),
+            EditCommand::DRAW_MODE {
+                index,
+                before,
+                after,
+            } => {
+                self.figure.get_mut(index).draw_mode = if revert { before } else { after };
+            }
+            EditCommand::MOVE { before, after } => {
+                self.figure.load_state(if revert { before } else { after });
+            }
+            EditCommand::STYLE {
+                index,
+                before,
+                after,
+            } => {
+                let (color, thickness) = if revert { before } else { after };
+                let edge = self.figure.get_mut(index);
+                edge.color = color;
+                edge.thickness = thickness;
+            }
+            EditCommand::OPEN { before, after } => {
+                self.figure.restore(if revert { before } else { after });
+            }
+        }
 
-            let mouse_pos = scissor.get_mouse_position();
+        self.figure.release();
+        self.cancel_tools();
+        self.unsaved = true;
+    }
 
-            // Drag background scroll
-            if scissor.is_mouse_button_down(MouseButton::MOUSE_BUTTON_MIDDLE)
-                && scissor_rec.check_collision_point_rec(mouse_pos)
-            {
-                let delta = mouse_pos.sub(self.previous_mouse_pos);
-                self.main_scroll.x += delta.x;
-                self.main_scroll.y += delta.y;
+    /// Cancel pending tools, so a restored tree is not edited by them
+    fn cancel_tools(&mut self) {
+        for btn in [&mut self.insert, &mut self.circle, &mut self.copy] {
+            btn.activated = false;
+        }
 
-                let max_scroll_x = texture_rec.width - scissor_rec.width;
-                let max_scroll_y = texture_rec.height - scissor_rec.height;
+        self.btn_pressed = false;
+        self.drag_start = None;
+        self.style_start = None;
+        self.dragging = false;
+        self.focused_edge = None;
+        self.figure.draw_option.point = true;
+    }
 
-                if self.main_scroll.x > 0.0 {
-                    self.main_scroll.x = 0.0;
-                } else if -self.main_scroll.x >= max_scroll_x {
-                    self.main_scroll.x = -max_scroll_x + 1.0;
-                }
+    pub fn undo(&mut self) {
+        match self.history.undo() {
+            Some(command) => {
+                let command = command.clone();
+                self.apply(command, true);
+            }
+            None => (),
+        }
+    }
 
-                if self.main_scroll.y > 0.0 {
-                    self.main_scroll.y = 0.0;
-                } else if -self.main_scroll.y >= max_scroll_y {
-                    self.main_scroll.y = -max_scroll_y + 1.0;
-                }
+    pub fn redo(&mut self) {
+        match self.history.redo() {
+            Some(command) => {
+                let command = command.clone();
+                self.apply(command, false);
             }
+            None => (),
+        }
+    }
 
-            texture_rec.x -= self.main_scroll.x;
-            texture_rec.y += self.main_scroll.y;
-            texture_rec.height = -texture_rec.height;
-            scissor.draw_texture_rec(
-                self.texture.texture(),
-                texture_rec,
-                Vector2::new(0.0, 0.0),
-                Color::RAYWHITE.fade(1.0),
+    pub fn draw(&mut self, handle: &mut RaylibDrawHandle, thread: &RaylibThread) {
+        let height = handle.get_screen_height() - self.start.y as i32;
+        // ===== Drawing figure =====
+        {
+            let mut draw_texture = handle.begin_texture_mode(thread, &mut self.texture);
+            draw_texture.clear_background(Color::RAYWHITE);
+            self.figure.draw(&mut draw_texture);
+        }
+        // ===== Drawing main Texture Screen =====
+        {
+            let main_rec = rrect(
+                self.sidebar_width,
+                self.start.y,
+                handle.get_screen_width() as f32 - self.sidebar_width,
+                handle.get_screen_height() as f32 - self.start.y,
             );
 
-            self.previous_mouse_pos = mouse_pos;
+            self.main_view.draw(handle, &self.texture, main_rec);
+            self.main_view
+                .draw_zoom_bar(handle, &mut [&mut self.fit_figure]);
         }
         if self.save_figure.activated {
             self.draw_save_dialog(handle, thread);
@@ -414,9 +680,21 @@ impl Edit {
             if self.format.text.is_none() {
                 self.format.set_icon(handle, VetorIcons::ICON_VERTEX_FORMAT);
             }
+            if self.undo.text.is_none() {
+                self.undo.set_icon(handle, VetorIcons::ICON_UNDO);
+            }
+            if self.redo.text.is_none() {
+                self.redo.set_icon(handle, VetorIcons::ICON_REDO);
+            }
             if self.save_figure.text.is_none() {
                 self.save_figure.text = Some(cstr!("Salvar"));
             }
+            if self.open_figure.text.is_none() {
+                self.open_figure.text = Some(cstr!("Abrir"));
+            }
+            if self.send_figure.text.is_none() {
+                self.send_figure.text = Some(cstr!("Animar"));
+            }
 
             for btn in [
                 &mut self.circle,
@@ -453,6 +731,13 @@ impl Edit {
                 }
             }
 
+            for btn in [&mut self.undo, &mut self.redo] {
+                btn.activated = handle.gui_button(
+                    rrect(btn.start.x, btn.start.y, btn.len, btn.len),
+                    Some(btn.text.clone().unwrap().as_c_str()),
+                );
+            }
+
             self.save_figure.activated = handle.gui_toggle(
                 rrect(
                     self.save_figure.start.x,
@@ -463,8 +748,101 @@ impl Edit {
                 Some(self.save_figure.text.clone().unwrap().as_c_str()),
                 self.save_figure.activated,
             );
+
+            self.open_figure.activated = handle.gui_button(
+                rrect(
+                    self.open_figure.start.x,
+                    self.open_figure.start.y,
+                    self.sidebar_width - 10.0,
+                    30,
+                ),
+                Some(self.open_figure.text.clone().unwrap().as_c_str()),
+            );
+
+            self.send_figure.activated = handle.gui_button(
+                rrect(
+                    self.send_figure.start.x,
+                    self.send_figure.start.y,
+                    self.sidebar_width - 10.0,
+                    30,
+                ),
+                Some(self.send_figure.text.clone().unwrap().as_c_str()),
+            ) || self.send_figure.activated;
+
+            self.draw_properties(handle);
         }
         // ===== END Drawing sidebar edit menu =====
+        dialog::draw_error(handle, &mut self.error);
+    }
+
+    /// Stroke color and thickness of the focused edge, disabled when there is none
+    fn draw_properties(&mut self, draw_handle: &mut RaylibDrawHandle) {
+        let panel = rrect(5, self.start.y + 420.0, self.sidebar_width - 10.0, 150);
+        let edge = self
+            .focused_edge
+            .filter(|index| *index < self.figure.tree.len());
+        let (color, thickness) = match edge {
+            Some(index) => (
+                self.figure.tree[index].color,
+                self.figure.tree[index].thickness,
+            ),
+            None => (COLOR, THICKNESS),
+        };
+
+        if edge.is_none() {
+            draw_handle.gui_disable();
+        }
+
+        draw_handle.gui_label(
+            rrect(panel.x, panel.y, panel.width, 20),
+            Some(rstr!("Traço")),
+        );
+        // The hue bar is drawn on the right of the picker
+        let picker = rrect(panel.x, panel.y + 25.0, 60, 60);
+        let picker_area = rrect(picker.x, picker.y, picker.width + 30.0, picker.height);
+        let alpha_bar = rrect(panel.x, panel.y + 95.0, panel.width, 12);
+        let thickness_bar = rrect(panel.x, panel.y + 132.0, panel.width, 15);
+
+        let picked = draw_handle.gui_color_picker(picker, None, Color::from(color));
+        let alpha = draw_handle.gui_color_bar_alpha(alpha_bar, None, color.a as f32 / 255.0);
+        draw_handle.gui_label(
+            rrect(panel.x, panel.y + 112.0, panel.width, 20),
+            Some(cstr!(format!("Espessura {}", thickness)).as_c_str()),
+        );
+        let picked_thickness =
+            draw_handle.gui_slider(thickness_bar, None, None, thickness, 1.0, 60.0);
+
+        draw_handle.gui_enable();
+
+        let index = match edge {
+            Some(index) => index,
+            None => return,
+        };
+
+        // The picker converts colors on every call, only apply values changed by the user
+        let mouse_pos = draw_handle.get_mouse_position();
+        let pressed = draw_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT);
+        let alpha = (alpha * 255.0).round() as u8;
+        let edge = self.figure.get_mut(index);
+
+        if pressed
+            && picker_area.check_collision_point_rec(mouse_pos)
+            && (picked.r, picked.g, picked.b) != (color.r, color.g, color.b)
+        {
+            edge.color = Rgba::new(picked.r, picked.g, picked.b, color.a);
+        }
+
+        if pressed && alpha_bar.check_collision_point_rec(mouse_pos) && alpha != color.a {
+            edge.color.a = alpha;
+        }
+
+        // Files may have thickness out of the slider range, keep it until the slider is moved
+        if pressed
+            && thickness_bar.check_collision_point_rec(mouse_pos)
+            && picked_thickness.round() != thickness
+        {
+            edge.thickness = picked_thickness.round();
+        }
     }
 
     fn draw_save_dialog(&mut self, draw_handle: &mut RaylibDrawHandle, thread: &RaylibThread) {
@@ -484,7 +862,7 @@ impl Edit {
             .fade(0.3),
         );
 
-        let dialog_rect = rrect((w / 2) - 150, (h / 2) - 100, 300, 90);
+        let dialog_rect = rrect((w / 2) - 150, (h / 2) - 100, 300, 130);
 
         self.save_figure.activated =
             !draw_handle.gui_window_box(dialog_rect, Some(rstr!("Salvar como:")));
@@ -506,32 +884,127 @@ impl Edit {
             rrect(dialog_rect.x + 154.0, dialog_rect.y + 40.0, 120, 30),
             Some(rstr!("Salvar")),
         ) {
-            self.save("unnamed");
+            if let Err(error) = self.save("unnamed") {
+                self.error = Some(error.to_string());
+            }
+
+            self.save_figure.activated = false;
+        }
+
+        if draw_handle.gui_button(
+            rrect(dialog_rect.x + 154.0, dialog_rect.y + 80.0, 120, 30),
+            Some(rstr!("Exportar SVG")),
+        ) {
+            if let Err(error) = self.export_svg("unnamed") {
+                self.error = Some(error.to_string());
+            }
+
             self.save_figure.activated = false;
         }
     }
 
-    fn save(&mut self, filename: &str) {
+    fn export_svg(&mut self, filename: &str) -> Result<()> {
+        let path = dialog::picked_path(
+            FileDialog::new()
+                .set_filename(&(filename.to_owned() + ".svg"))
+                .add_filter("Vector image", &["svg"])
+                .show_save_single_file(),
+        )?;
+
+        let path = match path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let content = svg::figure(&self.figure, Color::RAYWHITE.into());
+        fs::write(&path, content).map_err(|error| VetorError::io(&path, error))
+    }
+
+    fn save(&mut self, filename: &str) -> Result<()> {
         let extension = match self.encoding {
             FileEncoding::RAW => "vfr",
             FileEncoding::GZIP => "vfg",
             FileEncoding::ZLIB => "vfz",
         };
 
-        let path = FileDialog::new()
-            .set_filename(&(filename.to_owned() + "." + extension))
-            .add_filter("Vetor Figure", &["vfr", "vfg", "vfz"])
-            .show_save_single_file()
-            .expect("Cannot save file");
+        let path = dialog::picked_path(
+            FileDialog::new()
+                .set_filename(&(filename.to_owned() + "." + extension))
+                .add_filter("Vetor Figure", &["vfr", "vfg", "vfz"])
+                .show_save_single_file(),
+        )?;
 
-        if path.is_none() {
-            return;
+        let path = match path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        archives::export_figure(&path, self.figure.clone(), self.encoding)?;
+        self.path = Some(path);
+        self.unsaved = false;
+
+        Ok(())
+    }
+
+    /// Figure has changes not written to a file
+    pub fn unsaved(&self) -> bool {
+        self.unsaved
+    }
+
+    /// File of the figure, `None` until it is opened or saved
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Show the save dialog, `saving` is true until it is closed
+    pub fn show_save_dialog(&mut self) {
+        self.save_figure.activated = true;
+    }
+
+    pub fn saving(&self) -> bool {
+        self.save_figure.activated
+    }
+
+    /// Replace edited figure with a figure file, as an undoable action
+    fn open(&mut self) -> Result<()> {
+        let path = dialog::picked_path(
+            FileDialog::new()
+                .set_location("./src/assets/figures")
+                .add_filter("Vetor Figure", &["vfr", "vfg", "vfz"])
+                .show_open_single_file(),
+        )?;
+
+        let path = match path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        self.load_figure(archives::import_figure(&path)?);
+        self.path = Some(path);
+        // Opening is undoable, but the figure matches its file
+        self.unsaved = false;
+
+        Ok(())
+    }
+
+    /// Replace edited figure, centered on the canvas, as an undoable action
+    pub fn load_figure(&mut self, mut figure: Figure) {
+        figure.center_to(rvec2(BACKGROUND.0 / 2, BACKGROUND.1 / 2).into());
+
+        let before = self.figure.tree.clone();
+        self.cancel_tools();
+        self.figure.restore(figure.tree);
+        let after = self.figure.tree.clone();
+        self.record(EditCommand::OPEN { before, after });
+    }
+
+    /// Figure the user asked to send to the animation tab, once
+    pub fn take_sent_figure(&mut self) -> Option<Figure> {
+        if !self.send_figure.activated {
+            return None;
         }
 
-        archives::export_figure(
-            path.unwrap().to_str().unwrap(),
-            self.figure.clone(),
-            self.encoding,
-        );
+        self.send_figure.activated = false;
+        Some(self.figure.clone())
     }
 }
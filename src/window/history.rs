@@ -0,0 +1,101 @@
+/// Undo and redo stacks of commands already applied.
+/// Each tab decides how a command is reverted or applied again.
+pub struct History<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    limit: usize,
+}
+
+impl<T> History<T> {
+    pub fn new(limit: usize) -> History<T> {
+        History {
+            undo: vec![],
+            redo: vec![],
+            limit,
+        }
+    }
+
+    /// Register an applied command, dropping the commands that could be redone
+    pub fn push(&mut self, command: T) {
+        self.redo.clear();
+        self.undo.push(command);
+
+        if self.undo.len() > self.limit {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Move last command to redo stack and return it to be reverted
+    pub fn undo(&mut self) -> Option<&T> {
+        let command = self.undo.pop()?;
+        self.redo.push(command);
+        self.redo.last()
+    }
+
+    /// Move last undone command back to undo stack and return it to be applied
+    pub fn redo(&mut self) -> Option<&T> {
+        let command = self.redo.pop()?;
+        self.undo.push(command);
+        self.undo.last()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_and_redo_follow_push_order() {
+        let mut history = History::new(10);
+        history.push(1);
+        history.push(2);
+        history.push(3);
+
+        assert_eq!(history.undo(), Some(&3));
+        assert_eq!(history.undo(), Some(&2));
+        assert_eq!(history.redo(), Some(&2));
+        assert_eq!(history.redo(), Some(&3));
+        assert_eq!(history.redo(), None);
+        assert!(history.can_undo());
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn push_clears_redo() {
+        let mut history = History::new(10);
+        history.push(1);
+        history.push(2);
+        history.undo();
+        assert!(history.can_redo());
+
+        history.push(3);
+        assert!(!history.can_redo());
+        assert_eq!(history.undo(), Some(&3));
+        assert_eq!(history.undo(), Some(&1));
+    }
+
+    #[test]
+    fn oldest_command_is_dropped_over_limit() {
+        let mut history = History::new(2);
+        history.push(1);
+        history.push(2);
+        history.push(3);
+
+        assert_eq!(history.undo(), Some(&3));
+        assert_eq!(history.undo(), Some(&2));
+        assert_eq!(history.undo(), None);
+    }
+}
@@ -1,11 +1,12 @@
 pub mod animation;
 pub mod edit;
+pub mod history;
 pub mod tab;
 pub mod util;
 
 use self::animation::*;
 use self::edit::*;
-use self::tab::Tab;
+use self::tab::{Tab, TabKind};
 use self::util::button::*;
 use crate::archives;
 use crate::{cstr, maths::*};
@@ -19,32 +20,84 @@ use crate::{
     maths::vector2_rotate,
 };
 use raylib::{ffi::CheckCollisionPointRec, prelude::*};
-use std::{cell::RefCell, ffi::CString, fs, path::Path, rc::Rc};
+use std::{
+    cell::RefCell,
+    ffi::CString,
+    fs,
+    path::Path,
+    rc::{Rc, Weak},
+};
 
 pub const BACKGROUND: (u32, u32) = (1080 * 5, 720 * 5);
 
+/// Width of each tab in the tab menu
+const TAB_WIDTH: f32 = 100.0;
+
+/// Editor tab opened with a figure of an animation tab, so the edited
+/// figure can replace the one it was sent from.
+/// Figures are linked by their cell, global indexes shift when figures are
+/// added or removed, and cells are dropped when another animation is opened.
+struct FigureLink {
+    editor: Rc<RefCell<Tab>>,
+    animation: Rc<RefCell<Tab>>,
+    figure: Weak<RefCell<Figure>>,
+}
+
 pub struct Window {
     pub tabs: Vec<Rc<RefCell<Tab>>>,
     pub selected_tab: Rc<RefCell<Tab>>,
+    /// Tab whose close button was pressed in the tab menu
+    close_requested: Option<Rc<RefCell<Tab>>>,
+    /// Tab closed once its changes are saved, or discarded
+    closing: Option<Rc<RefCell<Tab>>>,
+    close_prompt: bool,
+    close_confirmed: bool,
+    /// Window close was requested, unsaved tabs are prompted one by one
+    quitting: bool,
+    /// Every tab was saved or discarded, the main loop can end
+    pub should_quit: bool,
+    new_tab: Button,
+    new_tab_kind: Option<TabKind>,
+    /// Last selected animation tab, receives figures sent from editors
+    active_animation: Option<Rc<RefCell<Tab>>>,
+    figure_links: Vec<FigureLink>,
+    /// Figure sent by a linked editor, waiting for the user to replace or add it
+    sent_back: Option<(Rc<RefCell<Tab>>, Figure)>,
+    /// Replace the linked figure with `sent_back`, otherwise add it as a new figure
+    replace_confirmed: Option<bool>,
 }
 
 impl Window {
     pub fn new(handle: &mut RaylibHandle, thread: &RaylibThread) -> Window {
-        let texture = handle
-            .load_render_texture(&thread, BACKGROUND.0, BACKGROUND.1)
-            .ok()
-            .unwrap();
-
-        let edit_tab = Rc::new(RefCell::new(Tab::Edit(Edit::new(handle, thread, texture))));
-        let animation_tab = Rc::new(RefCell::new(Tab::Animation(Animation::new(handle, thread))));
+        let edit_tab = Rc::new(RefCell::new(Tab::new(TabKind::EDIT, handle, thread)));
+        let animation_tab = Rc::new(RefCell::new(Tab::new(TabKind::ANIMATION, handle, thread)));
 
         Window {
             tabs: vec![edit_tab.clone(), animation_tab.clone()],
-            selected_tab: animation_tab,
+            selected_tab: animation_tab.clone(),
+            close_requested: None,
+            closing: None,
+            close_prompt: false,
+            close_confirmed: false,
+            quitting: false,
+            should_quit: false,
+            new_tab: Button::new(rvec2(0, 5)),
+            new_tab_kind: None,
+            active_animation: Some(animation_tab),
+            figure_links: vec![],
+            sent_back: None,
+            replace_confirmed: None,
         }
     }
 
     pub fn update(&mut self, handle: &mut RaylibHandle, thread: &RaylibThread) {
+        self.update_tabs(handle, thread);
+        self.update_sent_back(handle, thread);
+
+        if self.close_prompt || self.new_tab.activated || self.sent_back.is_some() {
+            return;
+        }
+
         if handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
             let mouse_pos = handle.get_mouse_position();
             let collision = unsafe {
@@ -55,7 +108,7 @@ impl Window {
             };
 
             if collision {
-                let width = 100;
+                let width = TAB_WIDTH as i32;
                 let height = 30;
 
                 for (i, tab) in self.tabs.iter().enumerate() {
@@ -76,17 +129,193 @@ impl Window {
 
         let mut tab = (*self.selected_tab).borrow_mut();
 
-        match &mut *tab {
+        // Figures go from editors to animations, and back for structural changes
+        let sent = match &mut *tab {
             Tab::Edit(page) => {
                 page.update(handle);
+                page.take_sent_figure()
+                    .map(|figure| (TabKind::ANIMATION, figure, None))
             }
             Tab::Animation(page) => {
                 page.update(handle, thread);
+                page.take_sent_figure()
+                    .map(|(source, figure)| (TabKind::EDIT, figure, Some(source)))
+            }
+        };
+
+        let is_animation = matches!(&*tab, Tab::Animation(_));
+        drop(tab);
+
+        if is_animation {
+            self.active_animation = Some(self.selected_tab.clone());
+        }
+
+        if let Some((kind, figure, source)) = sent {
+            self.figure_links
+                .retain(|link| link.figure.strong_count() > 0);
+            let linked = self
+                .figure_links
+                .iter()
+                .any(|link| Rc::ptr_eq(&link.editor, &self.selected_tab));
+
+            if kind == TabKind::ANIMATION && linked {
+                self.sent_back = Some((self.selected_tab.clone(), figure));
+            } else {
+                self.send_figure(kind, figure, source, handle, thread);
+            }
+        }
+    }
+
+    /// Add figure to the last selected animation, or open it in a new editor tab.
+    /// A new tab is created when there is no animation to receive it.
+    /// Editors opened with an animation figure remember where it came from.
+    fn send_figure(
+        &mut self,
+        kind: TabKind,
+        figure: Figure,
+        source: Option<Rc<RefCell<Figure>>>,
+        handle: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) {
+        let target = match (kind, &self.active_animation) {
+            (TabKind::ANIMATION, Some(tab)) => tab.clone(),
+            _ => {
+                let tab = Rc::new(RefCell::new(Tab::new(kind, handle, thread)));
+                self.tabs.push(tab.clone());
+                tab
+            }
+        };
+
+        match &mut *target.borrow_mut() {
+            Tab::Edit(page) => page.load_figure(figure),
+            Tab::Animation(page) => page.insert_figure(figure),
+        }
+
+        if let Some(figure) = source {
+            self.figure_links.push(FigureLink {
+                editor: target.clone(),
+                animation: self.selected_tab.clone(),
+                figure: Rc::downgrade(&figure),
+            });
+        }
+
+        self.selected_tab = target;
+    }
+
+    /// Replace the figure a linked editor came from, or add it as a new figure
+    fn update_sent_back(&mut self, handle: &mut RaylibHandle, thread: &RaylibThread) {
+        let replace = match self.replace_confirmed.take() {
+            Some(replace) => replace,
+            None => return,
+        };
+
+        let (editor, figure) = match self.sent_back.take() {
+            Some(sent) => sent,
+            None => return,
+        };
+
+        let link = self
+            .figure_links
+            .iter()
+            .find(|link| Rc::ptr_eq(&link.editor, &editor));
+
+        match link {
+            Some(link) if replace => {
+                if let Tab::Animation(page) = &mut *link.animation.borrow_mut() {
+                    match link.figure.upgrade() {
+                        Some(source) => page.replace_figure(&source, figure),
+                        None => page.insert_figure(figure),
+                    }
+                }
+
+                self.selected_tab = link.animation.clone();
+                self.active_animation = Some(link.animation.clone());
+            }
+            _ => self.send_figure(TabKind::ANIMATION, figure, None, handle, thread),
+        }
+    }
+
+    /// Close the window once every unsaved tab is saved or discarded
+    pub fn quit(&mut self) {
+        self.quitting = true;
+        self.new_tab.activated = false;
+    }
+
+    /// Open tabs chosen in the new tab menu and close tabs, asking to save them first
+    fn update_tabs(&mut self, handle: &mut RaylibHandle, thread: &RaylibThread) {
+        if let Some(kind) = self.new_tab_kind.take() {
+            let tab = Rc::new(RefCell::new(Tab::new(kind, handle, thread)));
+            self.tabs.push(tab.clone());
+            self.selected_tab = tab;
+        }
+
+        if let Some(tab) = self.close_requested.take() {
+            if tab.borrow().unsaved() {
+                self.selected_tab = tab.clone();
+                self.closing = Some(tab);
+                self.close_prompt = true;
+            } else {
+                self.close_tab(&tab);
+            }
+        }
+
+        // Ask for the next unsaved tab until none is left
+        if self.quitting && self.closing.is_none() && !self.close_prompt {
+            match self.tabs.iter().find(|tab| tab.borrow().unsaved()) {
+                Some(tab) => {
+                    self.selected_tab = tab.clone();
+                    self.closing = Some(tab.clone());
+                    self.close_prompt = true;
+                }
+                None => self.should_quit = true,
+            }
+        }
+
+        let tab = match &self.closing {
+            Some(tab) if !self.close_prompt => tab.clone(),
+            _ => return,
+        };
+
+        if self.close_confirmed || !tab.borrow().unsaved() {
+            self.close_confirmed = false;
+            self.closing = None;
+            self.close_tab(&tab);
+        } else if !tab.borrow().saving() {
+            // Save dialog was closed without saving
+            self.closing = None;
+            self.quitting = false;
+        }
+    }
+
+    /// Remove a tab, selecting its neighbour when it was selected.
+    /// The last tab is only closed when quitting.
+    fn close_tab(&mut self, tab: &Rc<RefCell<Tab>>) {
+        let index = match self.tabs.iter().position(|other| Rc::ptr_eq(other, tab)) {
+            Some(index) if self.tabs.len() > 1 || self.quitting => index,
+            _ => return,
+        };
+
+        self.tabs.remove(index);
+        self.figure_links
+            .retain(|link| !Rc::ptr_eq(&link.editor, tab) && !Rc::ptr_eq(&link.animation, tab));
+
+        if self.tabs.is_empty() {
+            self.should_quit = true;
+            return;
+        }
+
+        if let Some(animation) = &self.active_animation {
+            if Rc::ptr_eq(animation, tab) {
+                self.active_animation = None;
             }
         }
+
+        if Rc::ptr_eq(&self.selected_tab, tab) {
+            self.selected_tab = self.tabs[index.min(self.tabs.len() - 1)].clone();
+        }
     }
 
-    pub fn draw(&self, handle: &mut RaylibDrawHandle, thread: &RaylibThread) {
+    pub fn draw(&mut self, handle: &mut RaylibDrawHandle, thread: &RaylibThread) {
         // Draw current selected tab
         match &mut *(*self.selected_tab).borrow_mut() {
             Tab::Edit(page) => {
@@ -109,12 +338,9 @@ impl Window {
             ) as u32),
         );
         for (i, tab) in self.tabs.iter().enumerate() {
-            let text = match &*tab.borrow() {
-                Tab::Edit { .. } => rstr!("Editar"),
-                Tab::Animation { .. } => rstr!("Animar"),
-            };
+            let text = cstr!(tab.borrow().title());
             let icon = cstr!(handle.gui_icon_text(VetorIcons::ICON_CROSS.into(), None));
-            let width = 100.0;
+            let width = TAB_WIDTH;
             let x = width * i as f32;
 
             let is_selected = tab.as_ptr() == self.selected_tab.as_ptr();
@@ -134,7 +360,11 @@ impl Window {
                 GuiControlProperty::TEXT_PADDING as i32,
                 10,
             );
-            handle.gui_toggle(rrect(x, 0.0, width, 30.0), Some(text), is_selected);
+            handle.gui_toggle(
+                rrect(x, 0.0, width, 30.0),
+                Some(text.as_c_str()),
+                is_selected,
+            );
 
             handle.gui_set_style(
                 GuiControl::BUTTON,
@@ -151,7 +381,98 @@ impl Window {
                 GuiControlProperty::BORDER_WIDTH as i32,
                 1,
             );
-            handle.gui_button(rrect(x + 75.0, 5.0, 20.0, 20.0), Some(icon.as_c_str()));
+
+            // The last tab can't be closed
+            if self.tabs.len() > 1
+                && handle.gui_button(rrect(x + 75.0, 5.0, 20.0, 20.0), Some(icon.as_c_str()))
+                && !self.close_prompt
+            {
+                self.close_requested = Some(tab.clone());
+            }
+        }
+
+        self.draw_new_tab_menu(handle);
+
+        if self.close_prompt {
+            self.draw_close_prompt(handle);
+        } else if self.sent_back.is_some() {
+            self.draw_replace_prompt(handle);
+        }
+    }
+
+    /// Draw the new tab button after the tabs, it opens a menu with the tab kinds
+    fn draw_new_tab_menu(&mut self, handle: &mut RaylibDrawHandle) {
+        let x = TAB_WIDTH * self.tabs.len() as f32 + 5.0;
+
+        if handle.gui_button(rrect(x, self.new_tab.start.y, 20.0, 20.0), Some(rstr!("+"))) {
+            self.new_tab.activated = !self.new_tab.activated;
         }
+
+        if !self.new_tab.activated {
+            return;
+        }
+
+        let options = [
+            (TabKind::EDIT, rstr!("Figura")),
+            (TabKind::ANIMATION, rstr!("Animação")),
+        ];
+
+        for (i, (kind, text)) in options.into_iter().enumerate() {
+            if handle.gui_button(rrect(x, 30.0 + i as f32 * 30.0, 100.0, 30.0), Some(text)) {
+                self.new_tab_kind = Some(kind);
+                self.new_tab.activated = false;
+            }
+        }
+    }
+
+    /// Ask whether a figure sent back from its editor replaces the original one
+    fn draw_replace_prompt(&mut self, handle: &mut RaylibDrawHandle) {
+        let w = handle.get_screen_width();
+        let h = handle.get_screen_height();
+
+        let result = handle.gui_message_box(
+            rrect((w / 2) - 175, (h / 2) - 60, 350, 120),
+            Some(rstr!("Figura editada")),
+            Some(rstr!("Substituir a figura original na animação?")),
+            Some(rstr!("Substituir;Nova figura;Cancelar")),
+        );
+
+        // -1 while the box is open, 0 when closed on the window button
+        match result {
+            1 => self.replace_confirmed = Some(true),
+            2 => self.replace_confirmed = Some(false),
+            0 | 3 => self.sent_back = None,
+            _ => (),
+        }
+    }
+
+    /// Ask to save changes of the tab being closed
+    fn draw_close_prompt(&mut self, handle: &mut RaylibDrawHandle) {
+        let w = handle.get_screen_width();
+        let h = handle.get_screen_height();
+
+        let result = handle.gui_message_box(
+            rrect((w / 2) - 175, (h / 2) - 60, 350, 120),
+            Some(rstr!("Alterações não salvas")),
+            Some(rstr!("Salvar alterações antes de fechar?")),
+            Some(rstr!("Salvar;Descartar;Cancelar")),
+        );
+
+        // -1 while the box is open, 0 when closed on the window button
+        match result {
+            1 => {
+                if let Some(tab) = &self.closing {
+                    tab.borrow_mut().show_save_dialog();
+                }
+            }
+            2 => self.close_confirmed = true,
+            0 | 3 => {
+                self.closing = None;
+                self.quitting = false;
+            }
+            _ => return,
+        }
+
+        self.close_prompt = false;
     }
 }
@@ -1,6 +1,77 @@
-use super::{animation::Animation, edit::Edit};
+use super::{animation::Animation, edit::Edit, BACKGROUND};
+use raylib::prelude::*;
+use std::path::Path;
+
+/// Longest file name shown on a tab, longer names are cut
+const TITLE_LENGTH: usize = 10;
 
 pub enum Tab {
     Edit(Edit),
     Animation(Animation),
 }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TabKind {
+    EDIT,
+    ANIMATION,
+}
+
+impl Tab {
+    pub fn new(kind: TabKind, handle: &mut RaylibHandle, thread: &RaylibThread) -> Tab {
+        match kind {
+            TabKind::EDIT => {
+                let texture = handle
+                    .load_render_texture(thread, BACKGROUND.0, BACKGROUND.1)
+                    .ok()
+                    .unwrap();
+
+                Tab::Edit(Edit::new(handle, thread, texture))
+            }
+            TabKind::ANIMATION => Tab::Animation(Animation::new(handle, thread)),
+        }
+    }
+
+    /// File name of the tab document, marked with `*` while there are unsaved changes
+    pub fn title(&self) -> String {
+        let path = match self {
+            Tab::Edit(page) => page.path(),
+            Tab::Animation(page) => page.path(),
+        };
+
+        let mut name = path
+            .and_then(|path| Path::new(path).file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| String::from("sem título"));
+
+        if name.chars().count() > TITLE_LENGTH {
+            name = name.chars().take(TITLE_LENGTH - 2).collect::<String>() + "..";
+        }
+
+        if self.unsaved() {
+            format!("{}*", name)
+        } else {
+            name
+        }
+    }
+
+    pub fn unsaved(&self) -> bool {
+        match self {
+            Tab::Edit(page) => page.unsaved(),
+            Tab::Animation(page) => page.unsaved(),
+        }
+    }
+
+    pub fn show_save_dialog(&mut self) {
+        match self {
+            Tab::Edit(page) => page.show_save_dialog(),
+            Tab::Animation(page) => page.show_save_dialog(),
+        }
+    }
+
+    pub fn saving(&self) -> bool {
+        match self {
+            Tab::Edit(page) => page.saving(),
+            Tab::Animation(page) => page.saving(),
+        }
+    }
+}
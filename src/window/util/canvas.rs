@@ -0,0 +1,145 @@
+use super::button::Button;
+use crate::{cstr, maths::*};
+use raylib::prelude::*;
+use std::ffi::CString;
+use vetor_core::input::{Pointer, Viewport};
+
+/// Zoom factor of a single mouse wheel step
+const ZOOM_STEP: f32 = 1.1;
+/// Screen space kept around fitted figures and cameras
+const FIT_MARGIN: f32 = 40.0;
+
+/// Canvas texture shown on a screen area, moved by dragging the middle
+/// button and zoomed around the cursor by the mouse wheel.
+pub struct CanvasView {
+    pub view: Viewport,
+    canvas: (u32, u32),
+    /// Screen area the canvas is shown in, updated on every draw
+    pub area: Rectangle,
+    previous_mouse_pos: Vector2,
+    /// Zoom indicator, pressing it goes back to 100%
+    pub reset_zoom: Button,
+}
+
+impl CanvasView {
+    /// View of the canvas center at 100%
+    pub fn new(canvas: (u32, u32), area: Rectangle) -> CanvasView {
+        CanvasView {
+            view: Viewport::centered(canvas, (area.width, area.height)),
+            canvas,
+            area,
+            previous_mouse_pos: Vector2::zero(),
+            reset_zoom: Button::new(Vector2::zero()),
+        }
+    }
+
+    /// Canvas point under a screen position
+    pub fn to_canvas(&self, position: Vector2) -> Vec2 {
+        self.view
+            .to_canvas(position.sub(rvec2(self.area.x, self.area.y)).into())
+    }
+
+    /// Screen position of the canvas top left corner
+    pub fn origin(&self) -> Vec2 {
+        self.view.offset.add(Vec2::new(self.area.x, self.area.y))
+    }
+
+    /// Mouse pointer over the canvas, hit areas follow the zoom
+    pub fn pointer(&self, handle: &RaylibHandle) -> Pointer {
+        Pointer::from_handle(handle).zoomed(self.view.zoom)
+    }
+
+    /// Show a canvas rectangle as large as possible
+    pub fn fit(&mut self, rect: (f32, f32, f32, f32)) {
+        self.view
+            .fit(rect, (self.area.width, self.area.height), FIT_MARGIN);
+    }
+
+    /// Back to 100%, keeping the area center in place
+    pub fn reset(&mut self) {
+        let center = Vec2::new(self.area.width / 2.0, self.area.height / 2.0);
+        self.view.zoom_at(center, 1.0);
+    }
+
+    /// Pan and zoom with the mouse over the area, then draw the canvas texture on it
+    pub fn draw(
+        &mut self,
+        draw_handle: &mut RaylibDrawHandle,
+        texture: &RenderTexture2D,
+        area: Rectangle,
+    ) {
+        self.area = area;
+
+        let mouse_pos = draw_handle.get_mouse_position();
+        let position: Vec2 = mouse_pos.sub(rvec2(area.x, area.y)).into();
+
+        if area.check_collision_point_rec(mouse_pos) {
+            let wheel = draw_handle.get_mouse_wheel_move();
+
+            if wheel != 0.0 {
+                self.view
+                    .zoom_at(position, self.view.zoom * ZOOM_STEP.powf(wheel));
+            }
+
+            // Drag background scroll
+            if draw_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_MIDDLE) {
+                let delta = mouse_pos.sub(self.previous_mouse_pos);
+                self.view.offset = self.view.offset.add(delta.into());
+            }
+        }
+
+        self.view.clamp_to(self.canvas, (area.width, area.height));
+        self.previous_mouse_pos = mouse_pos;
+
+        draw_handle.draw_rectangle_rec(area, Color::LIGHTGRAY);
+
+        let mut scissor = draw_handle.begin_scissor_mode(
+            area.x as i32,
+            area.y as i32,
+            area.width as i32,
+            area.height as i32,
+        );
+
+        let origin = self.origin();
+        let (width, height) = (texture.texture.width as f32, texture.texture.height as f32);
+
+        // Invert texture rect height to apply correct perspective
+        scissor.draw_texture_pro(
+            texture.texture(),
+            rrect(0, 0, width, -height),
+            rrect(
+                origin.x,
+                origin.y,
+                width * self.view.zoom,
+                height * self.view.zoom,
+            ),
+            Vector2::zero(),
+            0.0,
+            Color::RAYWHITE.fade(1.0),
+        );
+    }
+
+    /// Zoom indicator on the bottom right corner of the area, with the fit buttons on its left
+    pub fn draw_zoom_bar(
+        &mut self,
+        draw_handle: &mut RaylibDrawHandle,
+        buttons: &mut [&mut Button],
+    ) {
+        let y = self.area.y + self.area.height - 34.0;
+        let mut x = self.area.x + self.area.width - 70.0;
+
+        self.reset_zoom.text = Some(cstr!(format!("{}%", (self.view.zoom * 100.0).round())));
+        self.reset_zoom.activated = draw_handle.gui_button(
+            rrect(x, y, 60, 24),
+            Some(self.reset_zoom.text.clone().unwrap().as_c_str()),
+        );
+
+        for btn in buttons.iter_mut() {
+            x -= 70.0;
+            btn.activated = draw_handle.gui_button(
+                rrect(x, y, 65, 24),
+                Some(btn.text.clone().unwrap().as_c_str()),
+            );
+        }
+    }
+}
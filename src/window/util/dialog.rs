@@ -0,0 +1,50 @@
+use crate::{
+    archives::error::{Result, VetorError},
+    cstr,
+};
+use raylib::prelude::*;
+use std::{ffi::CString, io, path::PathBuf};
+
+/// Path picked on a file dialog, `None` when the dialog is cancelled
+pub fn picked_path(picked: native_dialog::Result<Option<PathBuf>>) -> Result<Option<String>> {
+    let path = match picked {
+        Ok(Some(path)) => path,
+        Ok(None) => return Ok(None),
+        Err(error) => {
+            return Err(VetorError::io(
+                "File dialog",
+                io::Error::other(error.to_string()),
+            ))
+        }
+    };
+
+    match path.into_os_string().into_string() {
+        Ok(path) => Ok(Some(path)),
+        Err(path) => Err(VetorError::io(
+            &path.to_string_lossy(),
+            io::Error::new(io::ErrorKind::InvalidInput, "Path is not valid UTF-8"),
+        )),
+    }
+}
+
+/// Draw a message box with the error until it is dismissed
+pub fn draw_error(draw_handle: &mut RaylibDrawHandle, error: &mut Option<String>) {
+    let message = match error {
+        Some(message) => cstr!(message.replace('\0', "")),
+        None => return,
+    };
+
+    let w = draw_handle.get_screen_width();
+    let h = draw_handle.get_screen_height();
+
+    let result = draw_handle.gui_message_box(
+        rrect((w / 2) - 200, (h / 2) - 60, 400, 120),
+        Some(rstr!("Erro")),
+        Some(message.as_c_str()),
+        Some(rstr!("Ok")),
+    );
+
+    if result >= 0 {
+        *error = None;
+    }
+}
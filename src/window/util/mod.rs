@@ -1 +1,3 @@
 pub mod button;
+pub mod canvas;
+pub mod dialog;